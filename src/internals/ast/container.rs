@@ -8,7 +8,7 @@ use syn::{Attribute, Ident, Visibility};
 #[darling(
     attributes(openapi),
     forward_attrs,
-    supports(enum_unit, struct_named, struct_unit)
+    supports(enum_unit, enum_newtype, struct_named, struct_unit)
 )]
 pub struct Container {
     pub ident: Ident,
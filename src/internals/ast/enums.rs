@@ -1,6 +1,8 @@
 use crate::internals::ast::container::Container;
+use crate::internals::attrs::VariantValue;
+use darling::ast::Fields;
 use darling::FromVariant;
-use syn::{Attribute, Ident, Visibility};
+use syn::{Attribute, Expr, ExprLit, ExprUnary, Ident, Lit, UnOp, Visibility};
 
 #[derive(Clone, Debug, FromVariant)]
 #[darling(attributes(openapi), forward_attrs)]
@@ -8,12 +10,46 @@ pub struct Variant {
     pub ident: Ident,
     pub attrs: Vec<Attribute>,
     #[darling(default)]
-    pub value: Option<String>,
+    pub value: Option<VariantValue>,
+    pub discriminant: Option<Expr>,
+    pub fields: Fields<syn::Field>,
 }
 
 impl Variant {
+    /// The value the variant represents: the `value` attribute if it has one, otherwise its
+    /// discriminant or else its name.
     pub fn value(&self) -> String {
-        self.value.clone().unwrap_or_else(|| self.ident.to_string())
+        match (&self.value, self.discriminant_value()) {
+            (Some(value), _) => value.0.clone(),
+            (None, Some(discriminant)) => discriminant.to_string(),
+            (None, None) => self.ident.to_string(),
+        }
+    }
+
+    /// Whether the variant holds data, which is only allowed for the variant of an open enum
+    /// holding any other value.
+    pub fn is_catch_all(&self) -> bool {
+        !self.fields.style.is_unit()
+    }
+
+    /// The variant's discriminant, if it is an integer literal.
+    pub fn discriminant_value(&self) -> Option<i64> {
+        match self.discriminant.as_ref()? {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(int), ..
+            }) => int.base10_parse().ok(),
+            Expr::Unary(ExprUnary {
+                op: UnOp::Neg(_),
+                expr,
+                ..
+            }) => match expr.as_ref() {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(int), ..
+                }) => int.base10_parse::<i64>().ok().map(|value| -value),
+                _ => None,
+            },
+            _ => None,
+        }
     }
 }
 
@@ -25,6 +61,18 @@ pub struct EnumContainer {
     pub vis: Visibility,
 }
 
+impl EnumContainer {
+    /// Creates a container with no variants, as if the enum had been declared without any.
+    pub fn new(ident: Ident, vis: Visibility) -> Self {
+        Self {
+            ident,
+            attrs: vec![],
+            variants: vec![],
+            vis,
+        }
+    }
+}
+
 impl From<Container> for EnumContainer {
     fn from(container: Container) -> Self {
         Self {
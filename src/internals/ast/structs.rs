@@ -13,6 +13,9 @@ pub struct Field {
     pub name: Option<String>,
     #[darling(default)]
     pub allow_type_mismatch: Option<()>,
+    /// The name of the type generated for a schema declared inline in the field.
+    #[darling(default)]
+    pub type_name: Option<Ident>,
 }
 
 impl Field {
@@ -33,6 +36,19 @@ pub struct StructContainer {
     pub allow_type_mismatch: bool,
 }
 
+impl StructContainer {
+    /// Creates a container with no fields, as if the struct had been declared without any.
+    pub fn new(ident: Ident, vis: Visibility) -> Self {
+        Self {
+            ident,
+            attrs: vec![],
+            fields: vec![],
+            vis,
+            allow_type_mismatch: false,
+        }
+    }
+}
+
 impl From<Container> for StructContainer {
     fn from(container: Container) -> Self {
         Self {
@@ -1,8 +1,95 @@
 use darling::FromMeta;
+use std::collections::HashMap;
+use syn::{Lit, Path};
+
+/// How `anyOf` schemas are generated.
+#[derive(Clone, Copy, Debug, Default, FromMeta, PartialEq)]
+#[darling(rename_all = "snake_case")]
+pub enum AnyOf {
+    /// An untagged enum with a variant for each alternative.
+    #[default]
+    Enum,
+    /// A struct with every property of the alternatives as an optional field, when they are all
+    /// objects.
+    Struct,
+}
+
+/// The order fields and variants are declared in.
+#[derive(Clone, Copy, Debug, Default, FromMeta, PartialEq)]
+#[darling(rename_all = "snake_case")]
+pub enum Order {
+    /// The order the schema declares them in.
+    #[default]
+    Spec,
+    /// Sorted by name, or by value for integer enums.
+    Alphabetical,
+}
+
+/// The value of an enum variant, given as either a string or an integer, for example
+/// `value = "foo"` or `value = 5`.
+#[derive(Clone, Debug)]
+pub struct VariantValue(pub String);
+
+impl FromMeta for VariantValue {
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Str(s) => Ok(Self(s.value())),
+            Lit::Int(i) => Ok(Self(i.base10_digits().to_string())),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+}
 
 #[derive(Debug, FromMeta)]
 pub struct OpenApiArgs {
     pub schema: String,
     #[darling(default, rename = "ref")]
     pub reference: Option<String>,
+    /// Rust type paths to use in place of the default names for referenced components, for
+    /// example `rename(Pet = "crate::models::Pet")`.
+    #[darling(default)]
+    pub rename: HashMap<String, Path>,
+    /// Generates `Option<Option<T>>` for fields which are both optional and nullable, so that an
+    /// absent field can be told apart from one which is `null`.
+    #[darling(default)]
+    pub tri_state: bool,
+    /// Generates a field for each referenced member of an `allOf`, flattened when serialized,
+    /// rather than copying the member's properties into the struct. Members with optional
+    /// properties which another member requires are still copied.
+    #[darling(default)]
+    pub flatten_all_of: bool,
+    /// Either `any_of = "enum"`, the default, or `any_of = "struct"`.
+    #[darling(default)]
+    pub any_of: AnyOf,
+    /// Generates `BTreeMap` rather than `HashMap` for objects with additional properties.
+    #[darling(default)]
+    pub btree_map: bool,
+    /// Adds a variant to enums which holds any value the schema doesn't list, so that values
+    /// added to the API later can still be deserialized. It is called `Unknown(String)`, or
+    /// `Other(i64)` for integer enums, unless the schema lists a value of that name.
+    #[darling(default)]
+    pub open_enum: bool,
+    /// Generates unsigned integers for integer schemas with a `minimum` of zero or more, using the
+    /// smallest type which holds their `maximum`.
+    #[darling(default)]
+    pub infer_unsigned: bool,
+    /// Either `order = "spec"`, the default, or `order = "alphabetical"`.
+    #[darling(default)]
+    pub order: Order,
+    /// Rust type paths to use for values with a given format, for example
+    /// `formats(email = "crate::Email")`. Hyphens in format names are written as underscores.
+    #[darling(default)]
+    pub formats: HashMap<String, Path>,
+    /// Rust type paths to use for the components or formats with each name, for example
+    /// `type_map(Money = "rust_decimal::Decimal", int64 = "u64")`. Components mapped here aren't
+    /// generated, in the same way as those given in `rename`. A format mapped here takes
+    /// precedence over the same format in `formats`, but not over a schema's `x-rust-type`.
+    #[darling(default)]
+    pub type_map: HashMap<String, Path>,
+    /// The Rust type path to use for values the schema places no restrictions on, such as those
+    /// of an empty schema or of `additionalProperties: true`, for example
+    /// `any_type = "crate::Json"`. Defaults to `serde_json::Value`, which needs `serde_json` as a
+    /// dependency of the crate using the macro.
+    #[darling(default)]
+    pub any_type: Option<Path>,
 }
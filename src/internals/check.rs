@@ -1,35 +1,47 @@
 use crate::internals::ast::{EnumContainer, StructContainer};
-use crate::internals::error::CheckFailure;
+use crate::internals::codegen::TypeCodegen;
+use crate::internals::error::{CheckFailure, Located};
 use crate::internals::schema::{EnumSchema, StructSchema};
 use crate::internals::Error;
+use quote::ToTokens;
 use std::collections::HashSet;
+use syn::spanned::Spanned;
+use syn::{GenericArgument, Ident, PathArguments, Type};
 
+/// Checks a user defined type against its schema. Every discrepancy is collected, rather than
+/// stopping at the first, so they can all be reported together.
 pub trait Check<Schema> {
     fn check(&self, schema: &Schema) -> Result<(), Error>;
 }
 
 impl Check<EnumSchema> for EnumContainer {
     fn check(&self, schema: &EnumSchema) -> Result<(), Error> {
-        let ast_vars: HashSet<_> = self.variants.iter().map(|v| v.value()).collect();
+        // The catch-all variant of an open enum doesn't correspond to any value in the schema.
+        let variants: Vec<_> = self.variants.iter().filter(|v| !v.is_catch_all()).collect();
+        let ast_vars: HashSet<_> = variants.iter().map(|v| v.value()).collect();
         let schema_vars: HashSet<_> = schema.variants.iter().map(|v| v.value.clone()).collect();
 
-        if ast_vars == schema_vars {
-            Ok(())
-        } else if ast_vars.is_subset(&schema_vars) {
-            let mut variants: Vec<_> = schema_vars
-                .difference(&ast_vars)
-                .map(ToOwned::to_owned)
-                .collect();
-            variants.sort();
-            Err(Error::CheckFailed(CheckFailure::MissingVariants(variants)))
-        } else {
-            let mut variants: Vec<_> = ast_vars
-                .difference(&schema_vars)
-                .map(ToOwned::to_owned)
-                .collect();
-            variants.sort();
-            Err(Error::CheckFailed(CheckFailure::UnknownVariants(variants)))
+        let mut failures = vec![];
+
+        for variant in variants {
+            if !schema_vars.contains(&variant.value()) {
+                failures.push(Located::new(
+                    CheckFailure::UnknownVariant(variant.value()),
+                    variant.ident.span(),
+                ));
+            }
+        }
+
+        let mut missing: Vec<_> = schema_vars.difference(&ast_vars).collect();
+        missing.sort();
+        for variant in missing {
+            failures.push(Located::new(
+                CheckFailure::MissingVariant(variant.to_owned()),
+                self.ident.span(),
+            ));
         }
+
+        result(failures)
     }
 }
 
@@ -40,22 +52,153 @@ impl Check<StructSchema> for StructContainer {
         let schema_fields: HashSet<_, _> =
             schema.fields.iter().map(|f| f.name.to_owned()).collect();
 
-        if ast_fields == schema_fields {
-            Ok(())
-        } else if ast_fields.is_subset(&schema_fields) {
-            let mut fields: Vec<_> = schema_fields
-                .difference(&ast_fields)
-                .map(ToOwned::to_owned)
-                .collect();
-            fields.sort();
-            Err(Error::CheckFailed(CheckFailure::MissingFields(fields)))
-        } else {
-            let mut fields: Vec<_> = ast_fields
-                .difference(&schema_fields)
-                .map(ToOwned::to_owned)
-                .collect();
-            fields.sort();
-            Err(Error::CheckFailed(CheckFailure::UnknownFields(fields)))
+        let mut failures = vec![];
+
+        for field in &self.fields {
+            let name = field.field_name();
+            let field_schema = match schema.fields.iter().find(|f| f.name == name) {
+                Some(field_schema) => field_schema,
+                None => {
+                    failures.push(Located::new(
+                        CheckFailure::UnknownField(name),
+                        field.ident.span(),
+                    ));
+                    continue;
+                }
+            };
+
+            if self.allow_type_mismatch || field.allow_type_mismatch.is_some() {
+                continue;
+            }
+
+            let mut expected = TypeCodegen::from(field_schema);
+            if let Some(ident) = &field.type_name {
+                expected.set_nested(ident.clone());
+            }
+            let expected: Type = syn::parse_quote!(#expected);
+            if !types_match(&expected, &field.ty, field_schema.bounds) {
+                failures.push(Located::new(
+                    CheckFailure::MismatchedType {
+                        field: name,
+                        expected: type_string(&expected),
+                        found: type_string(&field.ty),
+                    },
+                    field.ty.span(),
+                ));
+            }
+        }
+
+        let mut missing: Vec<_> = schema_fields.difference(&ast_fields).collect();
+        missing.sort();
+        for field in missing {
+            failures.push(Located::new(
+                CheckFailure::MissingField(field.to_owned()),
+                self.ident.span(),
+            ));
+        }
+
+        result(failures)
+    }
+}
+
+fn result(failures: Vec<Located<CheckFailure>>) -> Result<(), Error> {
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::CheckFailed(failures))
+    }
+}
+
+/// Compares a generated type with one written by the user. Paths are compared by their last
+/// segment only, so `HashSet<T>` matches `std::collections::HashSet<T>` and a renamed component
+/// matches however it has been imported. Integers narrower than the generated type are accepted
+/// when they hold every value within the schema's `bounds`.
+fn types_match(expected: &Type, actual: &Type, bounds: Option<(i64, i64)>) -> bool {
+    match (expected, actual) {
+        (Type::Group(expected), _) => types_match(&expected.elem, actual, bounds),
+        (_, Type::Group(actual)) => types_match(expected, &actual.elem, bounds),
+        (Type::Paren(expected), _) => types_match(&expected.elem, actual, bounds),
+        (_, Type::Paren(actual)) => types_match(expected, &actual.elem, bounds),
+        (Type::Path(expected), Type::Path(actual)) if expected.qself.is_none() => {
+            match (expected.path.segments.last(), actual.path.segments.last()) {
+                (Some(expected), Some(actual)) => {
+                    (expected.ident == actual.ident
+                        || narrower(&expected.ident, &actual.ident, bounds))
+                        && arguments_match(&expected.arguments, &actual.arguments, bounds)
+                }
+                _ => false,
+            }
+        }
+        _ => type_string(expected) == type_string(actual),
+    }
+}
+
+fn arguments_match(
+    expected: &PathArguments,
+    actual: &PathArguments,
+    bounds: Option<(i64, i64)>,
+) -> bool {
+    match (expected, actual) {
+        (PathArguments::None, PathArguments::None) => true,
+        (PathArguments::AngleBracketed(expected), PathArguments::AngleBracketed(actual)) => {
+            expected.args.len() == actual.args.len()
+                && expected
+                    .args
+                    .iter()
+                    .zip(actual.args.iter())
+                    .all(|args| match args {
+                        (GenericArgument::Type(expected), GenericArgument::Type(actual)) => {
+                            types_match(expected, actual, bounds)
+                        }
+                        (expected, actual) => {
+                            expected.to_token_stream().to_string()
+                                == actual.to_token_stream().to_string()
+                        }
+                    })
+        }
+        (expected, actual) => {
+            expected.to_token_stream().to_string() == actual.to_token_stream().to_string()
         }
     }
 }
+
+/// Whether `actual` is an integer type narrower than `expected` which still holds every value
+/// within `bounds`, such as `u8` in place of `i32` for a schema with a `maximum` of 100. Without
+/// both bounds, only the generated type is known to hold every value the schema allows.
+fn narrower(expected: &Ident, actual: &Ident, bounds: Option<(i64, i64)>) -> bool {
+    fn range(ident: &Ident) -> Option<(i128, i128)> {
+        let range = match ident.to_string().as_str() {
+            "i8" => (i8::MIN.into(), i8::MAX.into()),
+            "i16" => (i16::MIN.into(), i16::MAX.into()),
+            "i32" => (i32::MIN.into(), i32::MAX.into()),
+            "i64" => (i64::MIN.into(), i64::MAX.into()),
+            "u8" => (0, u8::MAX.into()),
+            "u16" => (0, u16::MAX.into()),
+            "u32" => (0, u32::MAX.into()),
+            "u64" => (0, u64::MAX.into()),
+            _ => return None,
+        };
+        Some(range)
+    }
+
+    match (range(expected), range(actual), bounds) {
+        (Some((expected_min, expected_max)), Some((actual_min, actual_max)), Some(bounds)) => {
+            let (minimum, maximum) = (i128::from(bounds.0), i128::from(bounds.1));
+            expected_min <= actual_min
+                && actual_max <= expected_max
+                && actual_min <= minimum
+                && maximum <= actual_max
+        }
+        _ => false,
+    }
+}
+
+/// Formats a type roughly as it would be written, for use in error messages.
+fn type_string(ty: &Type) -> String {
+    ty.to_token_stream()
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(" < ", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+}
@@ -3,65 +3,86 @@ use crate::internals::attrs::OpenApiArgs;
 use crate::internals::check::Check;
 use crate::internals::codegen::{Enum, Struct};
 use crate::internals::error::Error;
-use crate::internals::schema::{EnumSchema, StructSchema};
-use crate::internals::utils::openapi_from_file;
+use crate::internals::resolver::Resolver;
+use crate::internals::schema::{EnumSchema, StructSchema, TryFromSchema};
+use crate::internals::spec::Spec;
 use darling::ast::Data;
 use darling::{FromDeriveInput, FromMeta};
-use openapiv3::{ReferenceOr, Schema};
 use proc_macro2::TokenStream;
-use std::convert::TryFrom;
 use syn::{AttributeArgs, DeriveInput};
 
 pub fn check_openapi(ast: &DeriveInput, attrs: &AttributeArgs) -> Result<TokenStream, Error> {
     let container: Container = Container::from_derive_input(ast)?;
     let args: OpenApiArgs = OpenApiArgs::from_list(attrs)?;
 
-    match &container.data {
-        Data::Enum(_) => check_openapi_enum(container.into(), args),
-        Data::Struct(_) => check_openapi_struct(container.into(), args),
-    }
-}
+    let spec = Spec::load(&args.schema)?;
+    let resolver = Resolver::new(&spec, &args);
 
-fn check_openapi_enum(cont: EnumContainer, args: OpenApiArgs) -> Result<TokenStream, Error> {
-    let schema = openapi_from_file(&args.schema)?;
-    let components = schema.components.ok_or(Error::MissingComponents)?;
+    let mut tokens = match &container.data {
+        Data::Enum(_) => check_openapi_enum(container.into(), &args, &resolver)?,
+        Data::Struct(_) => check_openapi_struct(container.into(), &args, &resolver)?,
+    };
+    tokens.extend(spec.dependency_tokens());
 
-    let reference = args.reference.unwrap_or_else(|| cont.ident.to_string());
+    Ok(tokens)
+}
 
-    let component: &Schema = match components.schemas.get(&reference) {
-        Some(ReferenceOr::Item(schema)) => schema,
-        _ => return Err(Error::ModelNotFound(reference).into()),
-    };
+fn check_openapi_enum(
+    cont: EnumContainer,
+    args: &OpenApiArgs,
+    resolver: &Resolver,
+) -> Result<TokenStream, Error> {
+    let reference = args
+        .reference
+        .clone()
+        .unwrap_or_else(|| cont.ident.to_string());
+    let (resolver, component) = resolver.component(&reference)?;
 
-    cont.check(&EnumSchema::try_from(component)?)?;
+    cont.check(&EnumSchema::try_from_schema(component, &resolver)?)?;
 
     Ok(Enum::from(cont).definition_tokens())
 }
 
-fn check_openapi_struct(cont: StructContainer, args: OpenApiArgs) -> Result<TokenStream, Error> {
-    let schema = openapi_from_file(&args.schema)?;
-    let components = schema.components.ok_or(Error::MissingComponents)?;
+fn check_openapi_struct(
+    cont: StructContainer,
+    args: &OpenApiArgs,
+    resolver: &Resolver,
+) -> Result<TokenStream, Error> {
+    let reference = args
+        .reference
+        .clone()
+        .unwrap_or_else(|| cont.ident.to_string());
+    let (resolver, component) = resolver.component(&reference)?;
+    let scope = cont.ident.to_string();
 
-    let reference = args.reference.unwrap_or_else(|| cont.ident.to_string());
-
-    let component: &Schema = match components.schemas.get(&reference) {
-        Some(ReferenceOr::Item(schema)) => schema,
-        _ => return Err(Error::ModelNotFound(reference).into()),
-    };
-
-    cont.check(&StructSchema::try_from(component)?)?;
+    cont.check(&StructSchema::try_from_schema(
+        component,
+        &resolver.scoped(&scope),
+    )?)?;
 
     Ok(Struct::from(cont).definition_tokens())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::internals::error::CheckFailure;
+    use crate::internals::error::{CheckFailure, Located};
+    use crate::internals::spec::Spec;
     use crate::internals::utils::parse2;
     use crate::internals::{check_openapi, Error};
+    use proc_macro2::Span;
     use quote::quote;
     use syn::{AttributeArgs, DeriveInput};
 
+    /// Spans aren't compared, so any will do for the expected failures.
+    fn check_failed(failures: Vec<CheckFailure>) -> Error {
+        Error::CheckFailed(
+            failures
+                .into_iter()
+                .map(|failure| Located::new(failure, Span::call_site()))
+                .collect(),
+        )
+    }
+
     #[test]
     fn test_enum_success() {
         let args = quote! { schema = "./test-resources/test_schema.yaml" };
@@ -79,6 +100,9 @@ mod tests {
         let args: AttributeArgs = parse2(args).unwrap();
         let input: DeriveInput = parse2(input).unwrap();
 
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
         let expected = quote! {
             #[derive(Clone, Debug)]
             enum TestEnum {
@@ -86,6 +110,77 @@ mod tests {
                 #[some_attribute]
                 Bar
             }
+            #dependencies
+        };
+
+        assert_eq!(
+            check_openapi(&input, &args).map(|s| s.to_string()),
+            Ok(expected.to_string())
+        )
+    }
+
+    #[test]
+    fn test_enum_integer() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            #[repr(u8)]
+            enum TestCode {
+                Created = 1,
+                #[openapi(value = 2)]
+                Accepted,
+                #[openapi(value = "5")]
+                NotFound,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            #[repr(u8)]
+            enum TestCode {
+                Created = 1,
+                Accepted,
+                NotFound
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            check_openapi(&input, &args).map(|s| s.to_string()),
+            Ok(expected.to_string())
+        )
+    }
+
+    #[test]
+    fn test_enum_open() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            enum TestEnum {
+                #[openapi(value = "foo")]
+                Foo,
+                #[openapi(value = "bar")]
+                Bar,
+                Unknown(String),
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            enum TestEnum {
+                Foo,
+                Bar,
+                Unknown(String)
+            }
+            #dependencies
         };
 
         assert_eq!(
@@ -111,9 +206,9 @@ mod tests {
 
         assert_eq!(
             check_openapi(&input, &args).map(|s| s.to_string()),
-            Err(Error::CheckFailed(CheckFailure::MissingVariants(vec![
+            Err(check_failed(vec![CheckFailure::MissingVariant(
                 "foo".to_string()
-            ])))
+            )]))
         )
     }
 
@@ -136,10 +231,212 @@ mod tests {
 
         assert_eq!(
             check_openapi(&input, &args).map(|s| s.to_string()),
-            Err(Error::CheckFailed(CheckFailure::UnknownVariants(vec![
-                "Foo".to_string(),
-                "Test".to_string()
-            ])))
+            Err(check_failed(vec![
+                CheckFailure::UnknownVariant("Test".to_string()),
+                CheckFailure::UnknownVariant("Foo".to_string()),
+                CheckFailure::MissingVariant("foo".to_string())
+            ]))
+        )
+    }
+
+    #[test]
+    fn test_struct_success() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestReferences {
+                test_struct: TestStruct,
+                #[openapi(name = "test_enums")]
+                enums: std::option::Option<Vec<crate::TestEnum>>,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestReferences {
+                test_struct: TestStruct,
+                enums: std::option::Option<Vec<crate::TestEnum> >
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            check_openapi(&input, &args).map(|s| s.to_string()),
+            Ok(expected.to_string())
+        )
+    }
+
+    #[test]
+    fn test_struct_nested() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestNested {
+                owner: Option<TestNestedOwner2>,
+                size: Option<TestNestedSize>,
+                #[openapi(type_name = "Tag")]
+                tags: Option<Vec<Tag>>,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        assert!(check_openapi(&input, &args).is_ok());
+    }
+
+    #[test]
+    fn test_struct_mismatched_types() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestStruct {
+                foo: i64,
+                bar: i32,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        assert_eq!(
+            check_openapi(&input, &args).map(|s| s.to_string()),
+            Err(check_failed(vec![
+                CheckFailure::MismatchedType {
+                    field: "foo".to_string(),
+                    expected: "String".to_string(),
+                    found: "i64".to_string()
+                },
+                CheckFailure::MismatchedType {
+                    field: "bar".to_string(),
+                    expected: "Option<i32>".to_string(),
+                    found: "i32".to_string()
+                }
+            ]))
+        )
+    }
+
+    #[test]
+    fn test_struct_narrower_integers() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestIntegers {
+                tiny: i8,
+                small: u8,
+                byte: u8,
+                count: u16,
+                total: u32,
+                percent: i8,
+                port: i16,
+                limit: u8,
+                size: i64,
+                offset: u64,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        assert_eq!(
+            check_openapi(&input, &args).map(|s| s.to_string()),
+            Err(check_failed(vec![
+                CheckFailure::MismatchedType {
+                    field: "small".to_string(),
+                    expected: "i16".to_string(),
+                    found: "u8".to_string()
+                },
+                CheckFailure::MismatchedType {
+                    field: "count".to_string(),
+                    expected: "u32".to_string(),
+                    found: "u16".to_string()
+                },
+                CheckFailure::MismatchedType {
+                    field: "total".to_string(),
+                    expected: "u64".to_string(),
+                    found: "u32".to_string()
+                },
+                CheckFailure::MismatchedType {
+                    field: "port".to_string(),
+                    expected: "i32".to_string(),
+                    found: "i16".to_string()
+                },
+                CheckFailure::MismatchedType {
+                    field: "size".to_string(),
+                    expected: "i32".to_string(),
+                    found: "i64".to_string()
+                },
+                CheckFailure::MismatchedType {
+                    field: "offset".to_string(),
+                    expected: "i64".to_string(),
+                    found: "u64".to_string()
+                }
+            ]))
         )
     }
+
+    #[test]
+    fn test_struct_allow_type_mismatch() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let field_input = quote! {
+            struct TestStruct {
+                #[openapi(allow_type_mismatch)]
+                foo: i64,
+                bar: Option<i32>,
+            }
+        };
+        let container_input = quote! {
+            #[openapi(allow_type_mismatch)]
+            struct TestStruct {
+                foo: i64,
+                bar: i32,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let field_input: DeriveInput = parse2(field_input).unwrap();
+        let container_input: DeriveInput = parse2(container_input).unwrap();
+
+        assert!(check_openapi(&field_input, &args).is_ok());
+        assert!(check_openapi(&container_input, &args).is_ok());
+    }
+
+    #[test]
+    fn test_struct_all_failures() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestReferences {
+                test_struct: String,
+                other: String,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let result = check_openapi(&input, &args);
+        assert_eq!(
+            result.as_ref().map(|s| s.to_string()),
+            Err(&check_failed(vec![
+                CheckFailure::MismatchedType {
+                    field: "test_struct".to_string(),
+                    expected: "TestStruct".to_string(),
+                    found: "String".to_string()
+                },
+                CheckFailure::UnknownField("other".to_string()),
+                CheckFailure::MissingField("test_enums".to_string())
+            ]))
+        );
+
+        let err: syn::Error = result.unwrap_err().into();
+        assert_eq!(
+            err.into_iter().map(|err| err.to_string()).collect::<Vec<_>>(),
+            vec![
+                "schema check failed due to mismatched type for field 'test_struct': expected `TestStruct`, found `String`",
+                "schema check failed due to unknown field 'other'",
+                "schema check failed due to missing field 'test_enums'",
+            ]
+        );
+    }
 }
@@ -0,0 +1,41 @@
+use crate::internals::codegen::docs;
+use crate::internals::codegen::types::TypeCodegen;
+use crate::internals::schema::{DocsSchema, TypeSchema};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Attribute, Ident, Visibility};
+
+#[derive(Debug)]
+pub struct Alias {
+    ident: Ident,
+    vis: Visibility,
+    attrs: Vec<Attribute>,
+    ty: TypeCodegen,
+}
+
+impl Alias {
+    /// Generates the type alias `ident` for `ty`, documented by `docs`.
+    pub fn new(ident: Ident, vis: Visibility, ty: &TypeSchema, docs: &DocsSchema) -> Self {
+        Self {
+            ident,
+            vis,
+            attrs: docs::doc_attrs(docs),
+            ty: TypeCodegen::from(ty),
+        }
+    }
+}
+
+impl ToTokens for Alias {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ident = &self.ident;
+        let vis = &self.vis;
+        let attrs = &self.attrs;
+        let ty = &self.ty;
+
+        (quote! {
+            #(#attrs)*
+            #vis type #ident = #ty;
+        })
+        .to_tokens(tokens);
+    }
+}
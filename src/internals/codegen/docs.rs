@@ -0,0 +1,96 @@
+use crate::internals::schema::DocsSchema;
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::{Attribute, Item};
+
+/// The doc comment for an item generated from a schema, one attribute per line, followed by
+/// `#[deprecated]` if the schema is.
+pub fn doc_attrs(docs: &DocsSchema) -> Vec<Attribute> {
+    fn paragraph(lines: &mut Vec<String>, text: &str) {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(text.lines().map(str::to_string));
+    }
+
+    let mut lines = vec![];
+
+    if let Some(title) = &docs.title {
+        paragraph(&mut lines, title);
+    }
+
+    if let Some(description) = &docs.description {
+        paragraph(&mut lines, description);
+    }
+
+    if let Some(external) = &docs.external_docs {
+        let link = match &external.description {
+            Some(description) => format!("See [{}]({}).", description, external.url),
+            None => format!("See <{}>.", external.url),
+        };
+        paragraph(&mut lines, &link);
+    }
+
+    if let Some(example) = &docs.example {
+        let example = serde_json::to_string_pretty(example).unwrap_or_default();
+        paragraph(&mut lines, "# Example");
+        paragraph(&mut lines, &format!("```json\n{}\n```", example));
+    }
+
+    let mut attrs: Vec<Attribute> = lines
+        .iter()
+        .map(|line| match line.as_str() {
+            "" => syn::parse_quote!(#[doc = ""]),
+            line => {
+                let line = format!(" {}", line);
+                syn::parse_quote!(#[doc = #line])
+            }
+        })
+        .collect();
+
+    if docs.deprecated {
+        attrs.push(syn::parse_quote!(#[deprecated]));
+    }
+
+    attrs
+}
+
+/// Combines the attributes generated from a schema with those the user wrote on the item. The
+/// user's doc comment replaces the schema's, as does their own `#[deprecated]`.
+pub fn merge_attrs(generated: Vec<Attribute>, user: Vec<Attribute>) -> Vec<Attribute> {
+    let documented = user.iter().any(|attr| attr.path.is_ident("doc"));
+    let deprecated = is_deprecated(&user);
+
+    generated
+        .into_iter()
+        .filter(|attr| {
+            !(documented && attr.path.is_ident("doc")
+                || deprecated && attr.path.is_ident("deprecated"))
+        })
+        .chain(user)
+        .collect()
+}
+
+/// Whether an item with `attrs` is deprecated.
+pub fn is_deprecated(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path.is_ident("deprecated"))
+}
+
+/// Allows `items`, generated alongside a deprecated type, to refer to it without warnings.
+pub fn allow_deprecated(items: TokenStream) -> TokenStream {
+    let mut file: syn::File = match syn::parse2(items.clone()) {
+        Ok(file) => file,
+        Err(_) => return items,
+    };
+
+    for item in &mut file.items {
+        let attrs = match item {
+            Item::Impl(item) => &mut item.attrs,
+            Item::Struct(item) => &mut item.attrs,
+            _ => continue,
+        };
+        attrs.push(syn::parse_quote!(#[allow(deprecated)]));
+    }
+
+    file.into_token_stream()
+}
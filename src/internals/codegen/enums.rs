@@ -1,10 +1,16 @@
 use crate::internals::ast::EnumContainer;
+use crate::internals::attrs::Order;
+use crate::internals::codegen::docs;
+use crate::internals::codegen::idents::variant_ident;
+#[cfg(feature = "serde")]
+use crate::internals::codegen::serde;
 use crate::internals::schema::{EnumSchema, VariantSchema};
 use crate::internals::{ast, Error};
-use heck::CamelCase;
-use proc_macro2::TokenStream;
+use darling::ast::Style;
+use proc_macro2::{Literal, TokenStream};
 use quote::ToTokens;
 use quote::{format_ident, quote};
+use std::collections::HashSet;
 use syn::{Attribute, Ident, Visibility};
 
 #[derive(Debug)]
@@ -12,6 +18,9 @@ pub struct Variant {
     name: Ident,
     attrs: Vec<Attribute>,
     value: String,
+    discriminant: Option<i64>,
+    /// The fields of the variant, which only the catch-all variant of an open enum has.
+    fields: TokenStream,
 }
 
 impl Variant {
@@ -19,24 +28,81 @@ impl Variant {
         self.name = other.name;
         self.attrs = other.attrs;
     }
+
+    /// Takes the name and attributes of the catch-all variant declared by the user, which must
+    /// hold the same type as the generated one.
+    fn update_catch_all(&mut self, other: Variant) -> Result<(), Error> {
+        if other.fields.to_string() != self.fields.to_string() {
+            return Err(Error::MismatchedCatchAll(
+                other.name.to_string(),
+                format!("{}{}", other.name, self.fields),
+            ));
+        }
+
+        self.update(other);
+        Ok(())
+    }
+
+    /// The variant of an open enum holding any value the schema doesn't list, named so as not to
+    /// clash with any of the `variants` it does.
+    fn catch_all(integer: bool, variants: &[Variant]) -> Result<Self, Error> {
+        let (names, fields) = if integer {
+            (["Other", "Unknown"], quote! {(i64)})
+        } else {
+            (["Unknown", "Other"], quote! {(String)})
+        };
+
+        let name = names
+            .iter()
+            .find(|name| !variants.iter().any(|variant| variant.name == name))
+            .ok_or_else(|| Error::DuplicateVariant(names[0].to_string()))?;
+
+        Ok(Self {
+            name: format_ident!("{}", name),
+            attrs: vec![],
+            value: String::new(),
+            discriminant: None,
+            fields,
+        })
+    }
 }
 
 impl From<&ast::Variant> for Variant {
     fn from(variant: &ast::Variant) -> Self {
+        let fields = &variant.fields.fields;
+        let fields = match variant.fields.style {
+            Style::Tuple => quote! {(#(#fields),*)},
+            Style::Struct => quote! {{#(#fields),*}},
+            Style::Unit => TokenStream::new(),
+        };
+
         Self {
             name: variant.ident.clone(),
             attrs: variant.attrs.clone(),
             value: variant.value(),
+            discriminant: variant.discriminant_value(),
+            fields,
         }
     }
 }
 
 impl From<&VariantSchema> for Variant {
     fn from(variant: &VariantSchema) -> Self {
+        // Integers aren't identifiers, so their variants are named after them instead.
+        let name = match variant.discriminant {
+            Some(discriminant) if discriminant < 0 => {
+                format_ident!("ValueMinus{}", discriminant.unsigned_abs())
+            }
+            Some(discriminant) => format_ident!("Value{}", discriminant as u64),
+            None => variant_ident(&variant.value),
+        };
+
         Self {
-            name: format_ident!("{}", variant.value.to_camel_case()),
+            name,
             attrs: vec![],
             value: variant.value.clone(),
+            discriminant: variant.discriminant,
+            fields: TokenStream::new(),
         }
     }
 }
@@ -45,11 +111,17 @@ impl ToTokens for Variant {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let name = &self.name;
         let attrs = &self.attrs;
+        let fields = &self.fields;
         (quote! {
             #(#attrs)*
-            #name
+            #name #fields
         })
         .to_tokens(tokens);
+
+        if let Some(discriminant) = self.discriminant {
+            let discriminant = Literal::i64_unsuffixed(discriminant);
+            (quote! { = #discriminant }).to_tokens(tokens);
+        }
     }
 }
 
@@ -59,37 +131,94 @@ pub struct Enum {
     variants: Vec<Variant>,
     vis: Visibility,
     attrs: Vec<Attribute>,
+    /// The catch-all variant, if the enum is open.
+    other: Option<Variant>,
+    /// The names of the variants, in the order the schema lists them.
+    all: Vec<Ident>,
 }
 
 impl Enum {
     pub fn combined(container: ast::EnumContainer, schema: EnumSchema) -> Result<Self, Error> {
+        let integer = schema.variants.iter().any(|v| v.discriminant.is_some());
+        let variants: Vec<_> = schema.variants.iter().map(Variant::from).collect();
+        let other = schema
+            .open
+            .then(|| Variant::catch_all(integer, &variants))
+            .transpose()?;
         let mut enm = Self {
             name: container.ident,
-            variants: schema.variants.iter().map(Variant::from).collect(),
+            variants,
             vis: container.vis,
-            attrs: container.attrs,
+            attrs: docs::merge_attrs(docs::doc_attrs(&schema.docs), container.attrs),
+            other,
+            all: vec![],
         };
 
         for variant in &container.variants {
-            if let Some(v) = enm.get_mut_variant(&variant.value()) {
+            if variant.is_catch_all() {
+                match &mut enm.other {
+                    Some(other) => other.update_catch_all(variant.into())?,
+                    None => return Err(Error::InvalidEnumVariant(variant.ident.to_string())),
+                }
+            } else if let Some(v) = enm.get_mut_variant(&variant.value()) {
                 v.update(variant.into())
             } else {
                 return Err(Error::InvalidEnumVariant(variant.value()));
             }
         }
 
-        enm.variants.sort_by_key(|v| v.name.to_string());
+        let mut names = HashSet::new();
+        if let Some(variant) = enm.variants.iter().find(|v| !names.insert(&v.name)) {
+            return Err(Error::DuplicateVariant(variant.name.to_string()));
+        }
+
+        enm.all = enm.variants.iter().map(|v| v.name.clone()).collect();
+
+        if schema.order == Order::Alphabetical {
+            if enm.is_integer() {
+                enm.variants.sort_by_key(|v| v.discriminant);
+            } else {
+                enm.variants.sort_by_key(|v| v.name.to_string());
+            }
+        }
+
+        if enm.is_integer() && !enm.attrs.iter().any(|attr| attr.path.is_ident("repr")) {
+            enm.attrs.push(syn::parse_quote!(#[repr(i64)]));
+        }
+
+        #[cfg(feature = "serde")]
+        enm.add_serde_attrs();
 
         Ok(enm)
     }
 
-    fn get_mut_variant(&mut self, value: &str) -> Option<&mut Variant> {
+    /// Whether the enum was generated from an integer enumeration, and so has discriminants.
+    fn is_integer(&self) -> bool {
+        self.variants
+            .iter()
+            .any(|variant| variant.discriminant.is_some())
+    }
+
+    /// Integer and open enums are serialized by the impls emitted alongside the enum instead.
+    #[cfg(feature = "serde")]
+    fn add_serde_attrs(&mut self) {
+        if self.is_integer() || self.other.is_some() {
+            return;
+        }
+
+        serde::add_derives(&mut self.attrs);
+
         for variant in &mut self.variants {
-            if variant.value == value {
-                return Some(variant);
-            }
+            variant
+                .attrs
+                .extend(serde::rename(&variant.name, &variant.value));
         }
-        None
+    }
+
+    fn get_mut_variant(&mut self, value: &str) -> Option<&mut Variant> {
+        self.variants
+            .iter_mut()
+            .find(|variant| variant.value == value)
     }
 
     pub fn definition_tokens(&self) -> TokenStream {
@@ -100,7 +229,7 @@ impl Enum {
 
     fn definition_to_tokens(&self, tokens: &mut TokenStream) {
         let name = &self.name;
-        let variants = &self.variants;
+        let variants = self.variants.iter().chain(&self.other);
         let vis = &self.vis;
         let attrs = &self.attrs;
 
@@ -112,6 +241,158 @@ impl Enum {
         })
         .to_tokens(tokens);
     }
+
+    /// The name of each variant along with the literal it is serialized as.
+    #[cfg(feature = "serde")]
+    fn variant_values(&self) -> Vec<(&Ident, Literal)> {
+        self.variants
+            .iter()
+            .map(|variant| {
+                let value = match variant.discriminant {
+                    Some(discriminant) => Literal::i64_unsuffixed(discriminant),
+                    None => Literal::string(&variant.value),
+                };
+                (&variant.name, value)
+            })
+            .collect()
+    }
+
+    /// Implements `as_str`, `Display` and `FromStr` for a string enum, along with a constant
+    /// listing its variants. Open enums get their own `FromStr`, which can't fail.
+    fn string_impls(&self) -> TokenStream {
+        let name = &self.name;
+        let vis = &self.vis;
+        let all = &self.all;
+        let names: Vec<_> = self.variants.iter().map(|variant| &variant.name).collect();
+        let values: Vec<_> = self.variants.iter().map(|variant| &variant.value).collect();
+
+        let as_str = match &self.other {
+            Some(other) => {
+                let other = &other.name;
+                quote! {
+                    /// The value the variant represents in the schema.
+                    pub fn as_str(&self) -> &str {
+                        match self {
+                            #(Self::#names => #values,)*
+                            Self::#other(value) => value.as_str(),
+                        }
+                    }
+                }
+            }
+            None => quote! {
+                /// The value the variant represents in the schema.
+                pub fn as_str(&self) -> &'static str {
+                    match self {
+                        #(Self::#names => #values,)*
+                    }
+                }
+            },
+        };
+
+        let mut tokens = quote! {
+            impl #name {
+                /// Every variant, in the order the schema lists them.
+                pub const ALL: &'static [Self] = &[#(Self::#all),*];
+
+                #as_str
+            }
+
+            impl std::fmt::Display for #name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+        };
+
+        if self.other.is_none() {
+            let error = format_ident!("Parse{}Error", name);
+            let doc = format!(
+                "The error returned when parsing a string which isn't one of the values of [{}].",
+                name
+            );
+            let message = format!("unknown {} value `{{}}`", name);
+
+            (quote! {
+                impl std::str::FromStr for #name {
+                    type Err = #error;
+
+                    fn from_str(value: &str) -> Result<Self, Self::Err> {
+                        match value {
+                            #(#values => Ok(Self::#names),)*
+                            value => Err(#error {
+                                value: value.to_string(),
+                            }),
+                        }
+                    }
+                }
+
+                #[doc = #doc]
+                #[derive(Clone, Debug, PartialEq, Eq)]
+                #vis struct #error {
+                    value: String,
+                }
+
+                impl std::fmt::Display for #error {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        write!(f, #message, self.value)
+                    }
+                }
+
+                impl std::error::Error for #error {}
+            })
+            .to_tokens(&mut tokens);
+        }
+
+        tokens
+    }
+
+    /// Implements `FromStr` for an open enum, along with `From<i64>` if it is an integer enum.
+    fn open_impls(&self, other: &Ident) -> TokenStream {
+        let name = &self.name;
+        let names = self.variants.iter().map(|variant| &variant.name);
+
+        if self.is_integer() {
+            let values = self
+                .variants
+                .iter()
+                .filter_map(|variant| variant.discriminant)
+                .map(Literal::i64_unsuffixed);
+
+            quote! {
+                impl From<i64> for #name {
+                    fn from(value: i64) -> Self {
+                        match value {
+                            #(#values => Self::#names,)*
+                            value => Self::#other(value),
+                        }
+                    }
+                }
+
+                impl std::str::FromStr for #name {
+                    type Err = std::num::ParseIntError;
+
+                    fn from_str(value: &str) -> Result<Self, Self::Err> {
+                        value.parse::<i64>().map(Self::from)
+                    }
+                }
+            }
+        } else {
+            let values = self.variants.iter().map(|variant| &variant.value);
+
+            quote! {
+                impl std::str::FromStr for #name {
+                    type Err = std::convert::Infallible;
+
+                    fn from_str(value: &str) -> Result<Self, Self::Err> {
+                        Ok(match value {
+                            #(#values => Self::#names,)*
+                            value => Self::#other(value.to_string()),
+                        })
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl From<EnumContainer> for Enum {
@@ -121,6 +402,8 @@ impl From<EnumContainer> for Enum {
             variants: container.variants.iter().map(Variant::from).collect(),
             vis: container.vis,
             attrs: container.attrs,
+            other: None,
+            all: vec![],
         }
     }
 }
@@ -128,5 +411,42 @@ impl From<EnumContainer> for Enum {
 impl ToTokens for Enum {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         self.definition_to_tokens(tokens);
+
+        let mut impls = TokenStream::new();
+
+        if !self.is_integer() {
+            self.string_impls().to_tokens(&mut impls);
+        }
+
+        match &self.other {
+            Some(other) => {
+                self.open_impls(&other.name).to_tokens(&mut impls);
+
+                #[cfg(feature = "serde")]
+                serde::open_enum_impls(
+                    &self.name,
+                    &self.variant_values(),
+                    &other.name,
+                    self.is_integer(),
+                )
+                .to_tokens(&mut impls);
+            }
+            #[cfg(feature = "serde")]
+            None if self.is_integer() => {
+                let variants: Vec<_> = self
+                    .variants
+                    .iter()
+                    .filter_map(|variant| Some((&variant.name, variant.discriminant?)))
+                    .collect();
+                serde::integer_enum_impls(&self.name, &variants).to_tokens(&mut impls);
+            }
+            None => {}
+        }
+
+        if docs::is_deprecated(&self.attrs) {
+            impls = docs::allow_deprecated(impls);
+        }
+
+        impls.to_tokens(tokens);
     }
 }
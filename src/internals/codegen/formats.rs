@@ -0,0 +1,60 @@
+#[cfg(feature = "serde")]
+use crate::internals::codegen::serde;
+#[cfg(feature = "serde")]
+use crate::internals::codegen::TypeCodegen;
+use heck::SnakeCase;
+use proc_macro2::TokenStream;
+use quote::{format_ident, ToTokens};
+use std::collections::BTreeSet;
+use syn::Ident;
+
+/// The module holding the serde adapters used by a group of generated types, such as those of a
+/// single component and the types declared inline in it, or every component of a document. It is
+/// declared once alongside them, and only if any of them need it.
+#[derive(Debug)]
+#[cfg_attr(not(feature = "serde"), allow(unused))]
+pub struct Formats {
+    ident: Ident,
+    /// The names of the adapters for single values which are used.
+    leaves: BTreeSet<&'static str>,
+}
+
+impl Formats {
+    /// The module for every component of a document, generated in one go.
+    pub fn shared() -> Self {
+        Self::new(format_ident!("__formats"))
+    }
+
+    /// The module for the type `ident` and the types declared inline in it.
+    pub fn for_type(ident: &Ident) -> Self {
+        Self::new(format_ident!(
+            "__{}_formats",
+            ident.to_string().to_snake_case()
+        ))
+    }
+
+    fn new(ident: Ident) -> Self {
+        Self {
+            ident,
+            leaves: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the attribute serializing a field or variant holding `ty` with the adapters in the
+    /// module, if it needs them, which are then declared in it.
+    #[cfg(feature = "serde")]
+    pub fn attr(&mut self, ty: &TypeCodegen, optional: bool) -> Option<syn::Attribute> {
+        serde::format_leaves(ty, &mut self.leaves);
+        serde::formatted(ty, &self.ident, optional)
+    }
+}
+
+impl ToTokens for Formats {
+    #[cfg_attr(not(feature = "serde"), allow(unused))]
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        #[cfg(feature = "serde")]
+        if !self.leaves.is_empty() {
+            serde::formats_module(&self.ident, &self.leaves).to_tokens(tokens);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use heck::{CamelCase, SnakeCase};
+use proc_macro2::Span;
+use quote::format_ident;
+use syn::Ident;
+
+/// Names a variant after its value. Values needn't be identifiers, so any characters which can't
+/// appear in one are dropped, and names which don't start with a letter, such as `1`, or which are
+/// keywords, such as `Self`, are prefixed with `Value`.
+pub fn variant_ident(value: &str) -> Ident {
+    let name = identifier_chars(&value.to_camel_case());
+
+    if name.starts_with(char::is_alphabetic) && name != "Self" {
+        format_ident!("{}", name)
+    } else {
+        format_ident!("Value{}", name)
+    }
+}
+
+/// Names a field after its property. Any characters which can't appear in an identifier are
+/// dropped, and names which don't start with a letter, such as `1st`, are prefixed with `value_`.
+/// Keywords are raw identifiers, such as `r#type`, other than those which can't be, which are
+/// followed by `_`.
+pub fn field_ident(name: &str) -> Ident {
+    let name = identifier_chars(&name.to_snake_case());
+
+    if name.is_empty() {
+        format_ident!("value")
+    } else if !name.starts_with(char::is_alphabetic) {
+        format_ident!("value_{}", name)
+    } else if syn::parse_str::<Ident>(&name).is_ok() {
+        format_ident!("{}", name)
+    } else if ["crate", "self", "super"].contains(&name.as_str()) {
+        format_ident!("{}_", name)
+    } else {
+        Ident::new_raw(&name, Span::call_site())
+    }
+}
+
+fn identifier_chars(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || (!c.is_ascii() && c.is_alphabetic()))
+        .collect()
+}
@@ -1,7 +1,18 @@
+mod aliases;
+mod docs;
 mod enums;
+mod formats;
+mod idents;
+mod nested;
+#[cfg(feature = "serde")]
+mod serde;
 mod structs;
 mod types;
+mod unions;
 
-pub use enums::{Enum, Variant};
-pub use structs::{Field, Struct};
+pub use aliases::Alias;
+pub use enums::Enum;
+pub use formats::Formats;
+pub use structs::Struct;
 pub use types::TypeCodegen;
+pub use unions::Union;
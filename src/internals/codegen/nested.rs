@@ -0,0 +1,65 @@
+use crate::internals::ast::{EnumContainer, StructContainer};
+use crate::internals::codegen::{Alias, Enum, Formats, Struct, Union};
+use crate::internals::schema::{ComponentSchema, NestedSchema};
+use crate::internals::Error;
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::{Attribute, Ident, Visibility};
+
+/// A type generated for a schema declared inline, emitted alongside the type it is declared in.
+#[derive(Debug)]
+pub enum Nested {
+    Struct(Struct),
+    Enum(Enum),
+    Union(Union),
+    Alias(Alias),
+}
+
+impl Nested {
+    /// Generates `schema` as the type `ident`, which types nested within it are also renamed
+    /// after. Nested types derive the same traits as the type they are declared in, whose
+    /// attributes are `attrs`, and share its serde adapters in `formats`.
+    pub fn new(
+        ident: Ident,
+        vis: Visibility,
+        attrs: &[Attribute],
+        schema: &NestedSchema,
+        formats: &mut Formats,
+    ) -> Result<Self, Error> {
+        let derives: Vec<Attribute> = attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("derive"))
+            .cloned()
+            .collect();
+
+        match schema.renamed(&ident.to_string()).schema {
+            ComponentSchema::Struct(strct) => {
+                let mut container = StructContainer::new(ident, vis);
+                container.attrs = derives;
+                Struct::combined(container, strct, formats).map(Self::Struct)
+            }
+            ComponentSchema::Enum(enm) => {
+                let mut container = EnumContainer::new(ident, vis);
+                container.attrs = derives;
+                Enum::combined(container, enm).map(Self::Enum)
+            }
+            ComponentSchema::Union(union) => {
+                let mut container = EnumContainer::new(ident, vis);
+                container.attrs = derives;
+                Union::combined(container, union, formats).map(Self::Union)
+            }
+            ComponentSchema::Alias(ty, docs) => Ok(Self::Alias(Alias::new(ident, vis, &ty, &docs))),
+        }
+    }
+}
+
+impl ToTokens for Nested {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Struct(strct) => strct.to_tokens(tokens),
+            Self::Enum(enm) => enm.to_tokens(tokens),
+            Self::Union(union) => union.to_tokens(tokens),
+            Self::Alias(alias) => alias.to_tokens(tokens),
+        }
+    }
+}
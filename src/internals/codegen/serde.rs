@@ -0,0 +1,1158 @@
+use crate::internals::codegen::TypeCodegen;
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use std::collections::BTreeSet;
+use syn::ext::IdentExt;
+use syn::{parse_quote, Attribute, Ident, Meta, NestedMeta};
+
+/// Adds `#[derive(Serialize, Deserialize)]` to a container's attributes, skipping either trait if
+/// the container already derives it.
+pub fn add_derives(attrs: &mut Vec<Attribute>) {
+    let serialize = !derives(attrs, "Serialize");
+    let deserialize = !derives(attrs, "Deserialize");
+
+    match (serialize, deserialize) {
+        (true, true) => attrs.push(parse_quote!(#[derive(serde::Serialize, serde::Deserialize)])),
+        (true, false) => attrs.push(parse_quote!(#[derive(serde::Serialize)])),
+        (false, true) => attrs.push(parse_quote!(#[derive(serde::Deserialize)])),
+        (false, false) => {}
+    }
+}
+
+/// Returns a `#[serde(rename = "...")]` attribute if `ident` would not be serialized as `name`.
+pub fn rename(ident: &Ident, name: &str) -> Option<Attribute> {
+    if ident.unraw() == name {
+        None
+    } else {
+        Some(parse_quote!(#[serde(rename = #name)]))
+    }
+}
+
+/// Returns the attribute for an `Option<Option<T>>` field of `container`, which deserializes a
+/// `null` value as `Some(None)` and an absent one as `None`.
+pub fn deserialize_nullable(container: &Ident) -> Attribute {
+    let path = format!("{}::__deserialize_nullable", container);
+    parse_quote!(#[serde(default, deserialize_with = #path)])
+}
+
+/// The function used by [deserialize_nullable]. Serde deserializes `null` as `None` for any
+/// `Option`, so a present value has to be wrapped in `Some` by hand.
+pub fn nullable_helper(container: &Ident) -> TokenStream {
+    quote! {
+        impl #container {
+            #[doc(hidden)]
+            fn __deserialize_nullable<'de, D, T>(
+                deserializer: D,
+            ) -> Result<Option<Option<T>>, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+                T: serde::Deserialize<'de>,
+            {
+                <Option<T> as serde::Deserialize>::deserialize(deserializer).map(Some)
+            }
+        }
+    }
+}
+
+/// Implements `Serialize` and `Deserialize` for an enum of `variants` with explicit discriminants,
+/// which are serialized as integers in the same way as `serde_repr`.
+pub fn integer_enum_impls(container: &Ident, variants: &[(&Ident, i64)]) -> TokenStream {
+    let names: Vec<_> = variants.iter().map(|(name, _)| name).collect();
+    let values: Vec<_> = variants
+        .iter()
+        .map(|(_, value)| Literal::i64_unsuffixed(*value))
+        .collect();
+    let expected = format!(
+        "one of {}",
+        variants
+            .iter()
+            .map(|(_, value)| value.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let integer = deserialize_integer();
+
+    quote! {
+        impl serde::Serialize for #container {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                let value: i64 = match self {
+                    #(Self::#names => #values),*
+                };
+                serializer.serialize_i64(value)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for #container {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                match #integer? {
+                    #(#values => Ok(Self::#names),)*
+                    other => Err(serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Signed(other),
+                        &#expected,
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// An expression deserializing an `i64` from `deserializer`. Some formats, and JSON written by
+/// some languages, give integers as unsigned or as floats such as `5.0`, so those are accepted if
+/// they hold an `i64`.
+fn deserialize_integer() -> TokenStream {
+    quote! {
+        {
+            struct IntegerVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for IntegerVisitor {
+                type Value = i64;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str("an integer")
+                }
+
+                fn visit_i64<E>(self, value: i64) -> Result<i64, E>
+                where
+                    E: serde::de::Error,
+                {
+                    Ok(value)
+                }
+
+                fn visit_u64<E>(self, value: u64) -> Result<i64, E>
+                where
+                    E: serde::de::Error,
+                {
+                    std::convert::TryFrom::try_from(value).map_err(|_| {
+                        E::invalid_value(serde::de::Unexpected::Unsigned(value), &self)
+                    })
+                }
+
+                fn visit_f64<E>(self, value: f64) -> Result<i64, E>
+                where
+                    E: serde::de::Error,
+                {
+                    // `i64::MAX` can't be held exactly by an `f64`, so the upper bound is `2^63`.
+                    let (min, max) = (i64::MIN as f64, -(i64::MIN as f64));
+                    if value.fract() == 0.0 && value >= min && value < max {
+                        Ok(value as i64)
+                    } else {
+                        Err(E::invalid_value(serde::de::Unexpected::Float(value), &self))
+                    }
+                }
+            }
+
+            serde::Deserializer::deserialize_i64(deserializer, IntegerVisitor)
+        }
+    }
+}
+
+/// Implements `Serialize` and `Deserialize` for an open enum, whose variant `other` holds any value
+/// other than those of `variants`. Values are converted with the `as_str`, `FromStr` or `From<i64>`
+/// generated for the enum.
+pub fn open_enum_impls(
+    container: &Ident,
+    variants: &[(&Ident, Literal)],
+    other: &Ident,
+    integer: bool,
+) -> TokenStream {
+    if integer {
+        let names = variants.iter().map(|(name, _)| name);
+        let values = variants.iter().map(|(_, value)| value);
+        let integer = deserialize_integer();
+
+        quote! {
+            impl serde::Serialize for #container {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    let value: i64 = match self {
+                        #(Self::#names => #values,)*
+                        Self::#other(value) => *value,
+                    };
+                    serializer.serialize_i64(value)
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for #container {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    #integer.map(Self::from)
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl serde::Serialize for #container {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    serializer.serialize_str(self.as_str())
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for #container {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+                    <Self as std::str::FromStr>::from_str(&value).map_err(serde::de::Error::custom)
+                }
+            }
+        }
+    }
+}
+
+/// Implements `Serialize` and `Deserialize` for an internally tagged union of `variants`, given as
+/// their name, tag value and whether the alternative declares the `tag` property itself. Those
+/// which do are handed the whole object, tag included, and serialize it themselves. The object is
+/// buffered by [content_buffer] while its tag is read, so only serde is needed.
+pub fn tagged_union_impls(
+    container: &Ident,
+    tag: &str,
+    variants: &[(&Ident, &str, bool)],
+) -> TokenStream {
+    let values: Vec<_> = variants.iter().map(|(_, value, _)| value).collect();
+
+    // Alternatives which don't declare the tag are serialized alongside it.
+    let tagged = if variants.iter().all(|(_, _, declares_tag)| *declares_tag) {
+        TokenStream::new()
+    } else {
+        quote! {
+            #[derive(serde::Serialize)]
+            struct Tagged<'a, T> {
+                #[serde(rename = #tag)]
+                tag: &'static str,
+                #[serde(flatten)]
+                value: &'a T,
+            }
+        }
+    };
+
+    let serialize = variants.iter().map(|(name, value, declares_tag)| {
+        if *declares_tag {
+            quote! { Self::#name(value) => serde::Serialize::serialize(value, serializer) }
+        } else {
+            quote! {
+                Self::#name(value) => serde::Serialize::serialize(
+                    &Tagged { tag: #value, value },
+                    serializer,
+                )
+            }
+        }
+    });
+
+    let deserialize = variants.iter().map(|(name, value, declares_tag)| {
+        let untag = if *declares_tag {
+            TokenStream::new()
+        } else {
+            quote! {
+                entries.retain(|(key, _)| !matches!(key, Content::String(key) if key == #tag));
+            }
+        };
+
+        quote! {
+            #value => {
+                #untag
+                serde::Deserialize::deserialize(ContentDeserializer::<D::Error>::new(Content::Map(entries)))
+                    .map(Self::#name)
+            }
+        }
+    });
+
+    let buffer = content_buffer();
+
+    quote! {
+        impl serde::Serialize for #container {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                #tagged
+
+                match self {
+                    #(#serialize,)*
+                }
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for #container {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                #buffer
+
+                const VARIANTS: &[&str] = &[#(#values),*];
+
+                let mut entries = match <Content as serde::Deserialize>::deserialize(deserializer)? {
+                    Content::Map(entries) => entries,
+                    _ => return Err(serde::de::Error::custom("expected an object")),
+                };
+                let tag = entries.iter().find_map(|(key, value)| match (key, value) {
+                    (Content::String(key), Content::String(value)) if key == #tag => {
+                        Some(value.clone())
+                    }
+                    _ => None,
+                });
+                let tag = match tag {
+                    Some(tag) => tag,
+                    None => return Err(serde::de::Error::missing_field(#tag)),
+                };
+
+                match tag.as_str() {
+                    #(#deserialize)*
+                    other => Err(serde::de::Error::unknown_variant(other, VARIANTS)),
+                }
+            }
+        }
+    }
+}
+
+/// Items buffering any self-describing value as a `Content`, which can be inspected and then
+/// deserialized through a `ContentDeserializer`, in the same way as serde's own internally tagged
+/// enums. Serde keeps its buffer private, so generated code declares its own.
+fn content_buffer() -> TokenStream {
+    quote! {
+        enum Content {
+            Bool(bool),
+            I64(i64),
+            U64(u64),
+            F64(f64),
+            String(String),
+            Bytes(Vec<u8>),
+            Unit,
+            Some(Box<Content>),
+            Seq(Vec<Content>),
+            Map(Vec<(Content, Content)>),
+        }
+
+        struct ContentVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ContentVisitor {
+            type Value = Content;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("any value")
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<Content, E> {
+                Ok(Content::Bool(value))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Content, E> {
+                Ok(Content::I64(value))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Content, E> {
+                Ok(Content::U64(value))
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<Content, E> {
+                Ok(Content::F64(value))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Content, E> {
+                Ok(Content::String(value.to_owned()))
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Content, E> {
+                Ok(Content::Bytes(value.to_owned()))
+            }
+
+            fn visit_unit<E>(self) -> Result<Content, E> {
+                Ok(Content::Unit)
+            }
+
+            fn visit_none<E>(self) -> Result<Content, E> {
+                Ok(Content::Unit)
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<Content, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                serde::Deserialize::deserialize(deserializer).map(|value| Content::Some(Box::new(value)))
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Content, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                serde::Deserialize::deserialize(deserializer)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Content, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut items = Vec::new();
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(Content::Seq(items))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Content, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Content::Map(entries))
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for Content {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_any(ContentVisitor)
+            }
+        }
+
+        struct ContentDeserializer<E> {
+            content: Content,
+            error: std::marker::PhantomData<E>,
+        }
+
+        impl<E> ContentDeserializer<E> {
+            fn new(content: Content) -> Self {
+                Self {
+                    content,
+                    error: std::marker::PhantomData,
+                }
+            }
+        }
+
+        impl<'de, E: serde::de::Error> serde::de::IntoDeserializer<'de, E> for Content {
+            type Deserializer = ContentDeserializer<E>;
+
+            fn into_deserializer(self) -> ContentDeserializer<E> {
+                ContentDeserializer::new(self)
+            }
+        }
+
+        impl<'de, E: serde::de::Error> serde::Deserializer<'de> for ContentDeserializer<E> {
+            type Error = E;
+
+            fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, E>
+            where
+                V: serde::de::Visitor<'de>,
+            {
+                match self.content {
+                    Content::Bool(value) => visitor.visit_bool(value),
+                    Content::I64(value) => visitor.visit_i64(value),
+                    Content::U64(value) => visitor.visit_u64(value),
+                    Content::F64(value) => visitor.visit_f64(value),
+                    Content::String(value) => visitor.visit_string(value),
+                    Content::Bytes(value) => visitor.visit_byte_buf(value),
+                    Content::Unit => visitor.visit_unit(),
+                    Content::Some(value) => visitor.visit_some(ContentDeserializer::new(*value)),
+                    Content::Seq(items) => {
+                        let mut seq = serde::de::value::SeqDeserializer::new(items.into_iter());
+                        let value = visitor.visit_seq(&mut seq)?;
+                        seq.end()?;
+                        Ok(value)
+                    }
+                    Content::Map(entries) => {
+                        let mut map = serde::de::value::MapDeserializer::new(entries.into_iter());
+                        let value = visitor.visit_map(&mut map)?;
+                        map.end()?;
+                        Ok(value)
+                    }
+                }
+            }
+
+            fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, E>
+            where
+                V: serde::de::Visitor<'de>,
+            {
+                match self.content {
+                    Content::Unit => visitor.visit_none(),
+                    Content::Some(value) => visitor.visit_some(ContentDeserializer::new(*value)),
+                    content => visitor.visit_some(ContentDeserializer::new(content)),
+                }
+            }
+
+            fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, E>
+            where
+                V: serde::de::Visitor<'de>,
+            {
+                visitor.visit_newtype_struct(self)
+            }
+
+            fn deserialize_enum<V>(
+                self,
+                _name: &'static str,
+                _variants: &'static [&'static str],
+                visitor: V,
+            ) -> Result<V::Value, E>
+            where
+                V: serde::de::Visitor<'de>,
+            {
+                match self.content {
+                    Content::String(value) => {
+                        visitor.visit_enum(serde::de::IntoDeserializer::into_deserializer(value))
+                    }
+                    Content::Map(entries) => visitor.visit_enum(
+                        serde::de::value::MapAccessDeserializer::new(
+                            serde::de::value::MapDeserializer::new(entries.into_iter()),
+                        ),
+                    ),
+                    _ => Err(serde::de::Error::custom("expected an enum")),
+                }
+            }
+
+            serde::forward_to_deserialize_any! {
+                bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+                byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+                ignored_any
+            }
+        }
+    }
+}
+
+/// The name of the adapter from [formats_module] which serializes `ty`, if it needs one. Neither
+/// `chrono` nor `time` serialize durations as ISO 8601 strings, `time` requires a fraction of a
+/// second in times and doesn't serialize date-times as RFC 3339 strings, and serde serializes
+/// bytes as an array of numbers rather than as base64, so these are formatted and parsed by hand.
+fn format_leaf(ty: &TypeCodegen) -> Option<&'static str> {
+    let time = cfg!(feature = "time") && !cfg!(feature = "chrono");
+
+    match ty {
+        TypeCodegen::Duration if cfg!(feature = "chrono") || time => Some("Duration"),
+        TypeCodegen::Date if time => Some("Date"),
+        TypeCodegen::DateTime if time => Some("DateTime"),
+        TypeCodegen::Time if time => Some("Time"),
+        TypeCodegen::Bytes => Some("Base64"),
+        _ => None,
+    }
+}
+
+/// Adds the names of the adapters `ty` needs to `leaves`.
+pub fn format_leaves(ty: &TypeCodegen, leaves: &mut BTreeSet<&'static str>) {
+    match ty {
+        TypeCodegen::Array(ty)
+        | TypeCodegen::Set(ty)
+        | TypeCodegen::Optional(ty)
+        | TypeCodegen::Nullable(ty)
+        | TypeCodegen::Map(ty)
+        | TypeCodegen::OrderedMap(ty)
+        | TypeCodegen::Alias(_, ty) => format_leaves(ty, leaves),
+        ty => leaves.extend(format_leaf(ty)),
+    }
+}
+
+/// The adapter from the module `formats` which serializes `ty`, if it holds any values which need
+/// formatting, however deeply they are nested.
+fn adapter(ty: &TypeCodegen, formats: &Ident) -> Option<TokenStream> {
+    let wrap = |name: &str, ty: &TypeCodegen| {
+        let name = format_ident!("{}", name);
+        adapter(ty, formats).map(|adapter| quote! {#formats::#name<#adapter>})
+    };
+
+    match ty {
+        TypeCodegen::Array(ty) => wrap("Array", ty),
+        TypeCodegen::Set(ty) => wrap("Set", ty),
+        TypeCodegen::Map(ty) => wrap("Map", ty),
+        TypeCodegen::OrderedMap(ty) => wrap("OrderedMap", ty),
+        // An optional field which may also be `null` is `Some` whenever it is present.
+        TypeCodegen::Optional(ty) if matches!(ty.as_ref(), TypeCodegen::Nullable(_)) => {
+            wrap("Present", ty)
+        }
+        TypeCodegen::Optional(ty) | TypeCodegen::Nullable(ty) => wrap("Nullable", ty),
+        TypeCodegen::Alias(_, ty) => adapter(ty, formats),
+        ty => format_leaf(ty).map(|name| {
+            let name = format_ident!("{}", name);
+            quote! {#formats::#name}
+        }),
+    }
+}
+
+/// Returns the attribute serializing a field or variant holding `ty` with the adapters in the
+/// module `formats`, if it needs them. Optional fields default to `None` when they are absent.
+pub fn formatted(ty: &TypeCodegen, formats: &Ident, optional: bool) -> Option<Attribute> {
+    let adapter = adapter(ty, formats)?;
+    let with = format!("{}::As::<{}>", formats, adapter).replace(' ', "");
+
+    if optional {
+        Some(parse_quote!(#[serde(default, with = #with)]))
+    } else {
+        Some(parse_quote!(#[serde(with = #with)]))
+    }
+}
+
+/// The module `formats`, holding the adapters named `leaves` along with those which apply them to
+/// the items of arrays, sets and maps and to the values of `Option`s. Fields are serialized with
+/// them using `#[serde(with = "formats::As::<Array<Date>>")]`, in the same way as `serde_with`.
+pub fn formats_module(formats: &Ident, leaves: &BTreeSet<&'static str>) -> TokenStream {
+    let leaves = leaves.iter().map(|leaf| leaf_adapter(leaf));
+
+    quote! {
+        #[doc(hidden)]
+        mod #formats {
+            use std::marker::PhantomData;
+
+            /// Serializes and deserializes a `T` in a particular format.
+            pub trait Adapter<T> {
+                fn serialize<S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer;
+
+                fn deserialize<'de, D>(deserializer: D) -> Result<T, D::Error>
+                where
+                    D: serde::Deserializer<'de>;
+            }
+
+            /// Serializes a field with the adapter `A`, for use with `#[serde(with = "...")]`.
+            pub struct As<A>(PhantomData<A>);
+
+            impl<A> As<A> {
+                pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    A: Adapter<T>,
+                    S: serde::Serializer,
+                {
+                    A::serialize(value, serializer)
+                }
+
+                pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+                where
+                    A: Adapter<T>,
+                    D: serde::Deserializer<'de>,
+                {
+                    A::deserialize(deserializer)
+                }
+            }
+
+            struct Ser<'a, T, A>(&'a T, PhantomData<A>);
+
+            impl<'a, T, A: Adapter<T>> serde::Serialize for Ser<'a, T, A> {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    A::serialize(self.0, serializer)
+                }
+            }
+
+            struct De<T, A>(T, PhantomData<A>);
+
+            impl<'de, T, A: Adapter<T>> serde::Deserialize<'de> for De<T, A> {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    A::deserialize(deserializer).map(|value| De(value, PhantomData))
+                }
+            }
+
+            /// An `Option` holding `None` for `null`.
+            pub struct Nullable<A>(PhantomData<A>);
+
+            impl<T, A: Adapter<T>> Adapter<Option<T>> for Nullable<A> {
+                fn serialize<S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    match value {
+                        Some(value) => serializer.serialize_some(&Ser::<T, A>(value, PhantomData)),
+                        None => serializer.serialize_none(),
+                    }
+                }
+
+                fn deserialize<'de, D>(deserializer: D) -> Result<Option<T>, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    <Option<De<T, A>> as serde::Deserialize>::deserialize(deserializer)
+                        .map(|value| value.map(|De(value, _)| value))
+                }
+            }
+
+            /// An `Option` holding `Some` whenever the field is present, even if it is `null`.
+            pub struct Present<A>(PhantomData<A>);
+
+            impl<T, A: Adapter<T>> Adapter<Option<T>> for Present<A> {
+                fn serialize<S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    match value {
+                        Some(value) => A::serialize(value, serializer),
+                        None => serializer.serialize_none(),
+                    }
+                }
+
+                fn deserialize<'de, D>(deserializer: D) -> Result<Option<T>, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    A::deserialize(deserializer).map(Some)
+                }
+            }
+
+            pub struct Array<A>(PhantomData<A>);
+
+            impl<T, A: Adapter<T>> Adapter<Vec<T>> for Array<A> {
+                fn serialize<S>(value: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    serializer.collect_seq(value.iter().map(|value| Ser::<T, A>(value, PhantomData)))
+                }
+
+                fn deserialize<'de, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    <Vec<De<T, A>> as serde::Deserialize>::deserialize(deserializer)
+                        .map(|values| values.into_iter().map(|De(value, _)| value).collect())
+                }
+            }
+
+            pub struct Set<A>(PhantomData<A>);
+
+            impl<T, A> Adapter<std::collections::HashSet<T>> for Set<A>
+            where
+                T: Eq + std::hash::Hash,
+                A: Adapter<T>,
+            {
+                fn serialize<S>(
+                    value: &std::collections::HashSet<T>,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    serializer.collect_seq(value.iter().map(|value| Ser::<T, A>(value, PhantomData)))
+                }
+
+                fn deserialize<'de, D>(deserializer: D) -> Result<std::collections::HashSet<T>, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    <Vec<De<T, A>> as serde::Deserialize>::deserialize(deserializer)
+                        .map(|values| values.into_iter().map(|De(value, _)| value).collect())
+                }
+            }
+
+            pub struct Map<A>(PhantomData<A>);
+
+            impl<T, A: Adapter<T>> Adapter<std::collections::HashMap<String, T>> for Map<A> {
+                fn serialize<S>(
+                    value: &std::collections::HashMap<String, T>,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    serializer.collect_map(
+                        value.iter().map(|(key, value)| (key, Ser::<T, A>(value, PhantomData))),
+                    )
+                }
+
+                fn deserialize<'de, D>(
+                    deserializer: D,
+                ) -> Result<std::collections::HashMap<String, T>, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    <std::collections::HashMap<String, De<T, A>> as serde::Deserialize>::deserialize(
+                        deserializer,
+                    )
+                    .map(|values| values.into_iter().map(|(key, De(value, _))| (key, value)).collect())
+                }
+            }
+
+            pub struct OrderedMap<A>(PhantomData<A>);
+
+            impl<T, A: Adapter<T>> Adapter<std::collections::BTreeMap<String, T>> for OrderedMap<A> {
+                fn serialize<S>(
+                    value: &std::collections::BTreeMap<String, T>,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    serializer.collect_map(
+                        value.iter().map(|(key, value)| (key, Ser::<T, A>(value, PhantomData))),
+                    )
+                }
+
+                fn deserialize<'de, D>(
+                    deserializer: D,
+                ) -> Result<std::collections::BTreeMap<String, T>, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    <std::collections::BTreeMap<String, De<T, A>> as serde::Deserialize>::deserialize(
+                        deserializer,
+                    )
+                    .map(|values| values.into_iter().map(|(key, De(value, _))| (key, value)).collect())
+                }
+            }
+
+            #(#leaves)*
+        }
+    }
+}
+
+/// The adapter called `name`, which formats and parses a single value as a string.
+fn leaf_adapter(name: &str) -> TokenStream {
+    let ident = format_ident!("{}", name);
+
+    // `time` already knows how to write RFC 3339 date-times, but only does so when asked.
+    if name == "DateTime" {
+        return quote! {
+            pub struct #ident;
+
+            impl Adapter<time::OffsetDateTime> for #ident {
+                fn serialize<S>(value: &time::OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    time::serde::rfc3339::serialize(value, serializer)
+                }
+
+                fn deserialize<'de, D>(deserializer: D) -> Result<time::OffsetDateTime, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    time::serde::rfc3339::deserialize(deserializer)
+                }
+            }
+        };
+    }
+
+    let (ty, format_body, parse_body, expected) = match name {
+        "Duration" => (
+            TypeCodegen::Duration,
+            format_duration(),
+            parse_duration(),
+            "an ISO 8601 duration",
+        ),
+        "Date" => (
+            TypeCodegen::Date,
+            format_date(),
+            parse_date(),
+            "an RFC 3339 date",
+        ),
+        "Base64" => (
+            TypeCodegen::Bytes,
+            format_base64(),
+            parse_base64(),
+            "a base64 string",
+        ),
+        _ => (
+            TypeCodegen::Time,
+            format_time(),
+            parse_time(),
+            "an RFC 3339 time",
+        ),
+    };
+    // Bytes are formatted from a slice, which a `Vec<u8>` dereferences to.
+    let formatted = match ty {
+        TypeCodegen::Bytes => quote! {[u8]},
+        ref ty => quote! {#ty},
+    };
+
+    quote! {
+        pub struct #ident;
+
+        impl #ident {
+            fn format(value: &#formatted) -> String {
+                #format_body
+            }
+
+            fn parse(value: &str) -> Option<#ty> {
+                #parse_body
+            }
+        }
+
+        impl Adapter<#ty> for #ident {
+            fn serialize<S>(value: &#ty, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_str(&Self::format(value))
+            }
+
+            fn deserialize<'de, D>(deserializer: D) -> Result<#ty, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+                Self::parse(&value).ok_or_else(|| {
+                    serde::de::Error::invalid_value(serde::de::Unexpected::Str(&value), &#expected)
+                })
+            }
+        }
+    }
+}
+
+/// Formats a duration as days, hours, minutes and seconds, for example `P1DT2H0.5S`.
+fn format_duration() -> TokenStream {
+    let parts = if cfg!(feature = "chrono") {
+        quote! { (value.num_seconds(), value.subsec_nanos()) }
+    } else {
+        quote! { (value.whole_seconds(), value.subsec_nanoseconds()) }
+    };
+
+    quote! {
+        let (seconds, nanoseconds) = #parts;
+        let sign = if seconds < 0 || nanoseconds < 0 { "-" } else { "" };
+        let (seconds, nanoseconds) = (seconds.unsigned_abs(), nanoseconds.unsigned_abs());
+        let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+        let (hours, seconds) = (seconds / 3_600, seconds % 3_600);
+        let (minutes, seconds) = (seconds / 60, seconds % 60);
+
+        let mut clock = String::new();
+        if hours > 0 {
+            clock += &format!("{}H", hours);
+        }
+        if minutes > 0 {
+            clock += &format!("{}M", minutes);
+        }
+        if nanoseconds > 0 {
+            let fraction = format!("{:09}", nanoseconds);
+            clock += &format!("{}.{}S", seconds, fraction.trim_end_matches('0'));
+        } else if seconds > 0 || (days == 0 && clock.is_empty()) {
+            clock += &format!("{}S", seconds);
+        }
+
+        match (days, clock.is_empty()) {
+            (0, _) => format!("{}PT{}", sign, clock),
+            (days, true) => format!("{}P{}D", sign, days),
+            (days, false) => format!("{}P{}DT{}", sign, days, clock),
+        }
+    }
+}
+
+/// Parses a duration made up of weeks, days, hours, minutes and seconds. Years and months are
+/// rejected, as their length varies.
+fn parse_duration() -> TokenStream {
+    let from_parts = if cfg!(feature = "chrono") {
+        quote! {
+            // `chrono` panics on durations of more than `i64::MAX` milliseconds.
+            if seconds > i64::MAX / 1_000 {
+                return None;
+            }
+            let duration =
+                chrono::Duration::seconds(seconds) + chrono::Duration::nanoseconds(nanoseconds);
+        }
+    } else {
+        quote! {
+            let duration = time::Duration::new(seconds, nanoseconds as i32);
+        }
+    };
+
+    quote! {
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (false, value),
+        };
+        let value = value.strip_prefix('P')?;
+        let (date, clock) = match value.split_once('T') {
+            Some((date, clock)) if !clock.is_empty() => (date, clock),
+            Some(_) => return None,
+            None if !value.is_empty() => (value, ""),
+            None => return None,
+        };
+
+        let mut seconds: i64 = 0;
+        let mut nanoseconds: i64 = 0;
+        let components: [(&str, &[(char, i64)]); 2] = [
+            (date, &[('W', 604_800), ('D', 86_400)]),
+            (clock, &[('H', 3_600), ('M', 60), ('S', 1)]),
+        ];
+
+        for (mut rest, units) in components {
+            while !rest.is_empty() {
+                let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+                let designator = rest[end..].chars().next()?;
+                let unit = units.iter().find(|(d, _)| *d == designator)?.1;
+                let number = &rest[..end];
+                rest = &rest[end + 1..];
+
+                // Only seconds may have a fraction, of up to nanosecond precision.
+                let (whole, fraction) = match number.split_once(|c| c == '.' || c == ',') {
+                    Some((whole, fraction)) if designator == 'S' => (whole, fraction),
+                    Some(_) => return None,
+                    None => (number, ""),
+                };
+                if whole.is_empty()
+                    || !whole.bytes().all(|b| b.is_ascii_digit())
+                    || fraction.len() > 9
+                    || !fraction.bytes().all(|b| b.is_ascii_digit())
+                {
+                    return None;
+                }
+
+                seconds = seconds.checked_add(whole.parse::<i64>().ok()?.checked_mul(unit)?)?;
+                if !fraction.is_empty() {
+                    nanoseconds = format!("{:0<9}", fraction).parse().ok()?;
+                }
+            }
+        }
+
+        #from_parts
+        Some(if negative { -duration } else { duration })
+    }
+}
+
+/// Formats a `time::Date` as `YYYY-MM-DD`.
+fn format_date() -> TokenStream {
+    quote! {
+        format!("{:04}-{:02}-{:02}", value.year(), u8::from(value.month()), value.day())
+    }
+}
+
+fn parse_date() -> TokenStream {
+    quote! {
+        let mut parts = value.splitn(3, '-');
+        let mut next = |len: usize| {
+            parts
+                .next()
+                .filter(|part| part.len() == len && part.bytes().all(|b| b.is_ascii_digit()))
+        };
+        let year = next(4)?.parse().ok()?;
+        let month = next(2)?.parse::<u8>().ok()?;
+        let day = next(2)?.parse().ok()?;
+        let month = std::convert::TryFrom::try_from(month).ok()?;
+        time::Date::from_calendar_date(year, month, day).ok()
+    }
+}
+
+/// Formats a `time::Time` as `HH:MM:SS`, followed by a fraction of a second if it has one.
+fn format_time() -> TokenStream {
+    quote! {
+        let (hour, minute, second, nanosecond) = value.as_hms_nano();
+        let mut clock = format!("{:02}:{:02}:{:02}", hour, minute, second);
+        if nanosecond > 0 {
+            let fraction = format!("{:09}", nanosecond);
+            clock += &format!(".{}", fraction.trim_end_matches('0'));
+        }
+        clock
+    }
+}
+
+fn parse_time() -> TokenStream {
+    quote! {
+        let (clock, fraction) = match value.split_once('.') {
+            Some((clock, fraction))
+                if !fraction.is_empty()
+                    && fraction.len() <= 9
+                    && fraction.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                (clock, format!("{:0<9}", fraction).parse().ok()?)
+            }
+            Some(_) => return None,
+            None => (value, 0),
+        };
+        let mut parts = clock.splitn(3, ':').map(|part| {
+            Some(part)
+                .filter(|part| part.len() == 2 && part.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|part| part.parse().ok())
+        });
+        let hour = parts.next()??;
+        let minute = parts.next()??;
+        let second = parts.next()??;
+        time::Time::from_hms_nano(hour, minute, second, fraction).ok()
+    }
+}
+
+/// Encodes bytes with the standard base64 alphabet, padded with `=`.
+fn format_base64() -> TokenStream {
+    quote! {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        let mut encoded = String::with_capacity(value.len() * 4 / 3 + 4);
+        for chunk in value.chunks(3) {
+            let bits = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |bits, (i, byte)| bits | (u32::from(*byte) << (16 - 8 * i)));
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    encoded.push(char::from(ALPHABET[((bits >> (18 - 6 * i)) & 63) as usize]));
+                } else {
+                    encoded.push('=');
+                }
+            }
+        }
+        encoded
+    }
+}
+
+/// Decodes base64 with either the standard or the URL safe alphabet, with or without padding.
+fn parse_base64() -> TokenStream {
+    quote! {
+        let unpadded = value.trim_end_matches('=');
+        if value.len() - unpadded.len() > 2 || (unpadded.len() < value.len() && value.len() % 4 != 0)
+        {
+            return None;
+        }
+
+        let mut decoded = Vec::with_capacity(unpadded.len() * 3 / 4);
+        let (mut bits, mut count) = (0u32, 0);
+        for c in unpadded.bytes() {
+            let digit = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' | b'-' => 62,
+                b'/' | b'_' => 63,
+                _ => return None,
+            };
+            bits = ((bits << 6) | u32::from(digit)) & 0xffff;
+            count += 6;
+            if count >= 8 {
+                count -= 8;
+                decoded.push((bits >> count) as u8);
+            }
+        }
+
+        // A single character left over doesn't make up a whole byte.
+        if count >= 6 {
+            return None;
+        }
+        Some(decoded)
+    }
+}
+
+/// Whether `attrs` contain a derive of a trait called `name`.
+fn derives(attrs: &[Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("derive"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|nested| match nested {
+            NestedMeta::Meta(meta) => meta
+                .path()
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == name),
+            NestedMeta::Lit(_) => false,
+        })
+}
@@ -1,10 +1,20 @@
 use crate::internals::ast::StructContainer;
+use crate::internals::attrs::Order;
+use crate::internals::codegen::docs;
+use crate::internals::codegen::formats::Formats;
+use crate::internals::codegen::idents::field_ident;
+use crate::internals::codegen::nested::Nested;
+#[cfg(feature = "serde")]
+use crate::internals::codegen::serde;
 use crate::internals::codegen::types::TypeCodegen;
 use crate::internals::schema::{FieldSchema, StructSchema};
 use crate::internals::{ast, Error};
-use heck::SnakeCase;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, ToTokens};
+#[cfg(feature = "serde")]
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use syn::ext::IdentExt;
 use syn::{Attribute, Ident, VisPublic, Visibility};
 
 #[derive(Debug)]
@@ -14,6 +24,11 @@ pub struct Field {
     ty: TypeCodegen,
     vis: Visibility,
     attrs: Vec<Attribute>,
+    /// Only used to choose how the field is serialized.
+    #[cfg_attr(not(feature = "serde"), allow(unused))]
+    flatten: bool,
+    /// The name of the type generated for a schema declared inline in the field.
+    nested: Option<Ident>,
 }
 
 impl Field {
@@ -21,7 +36,43 @@ impl Field {
         self.ident = other.ident;
         self.ty = other.ty;
         self.vis = other.vis;
-        self.attrs = other.attrs;
+        self.attrs = docs::merge_attrs(std::mem::take(&mut self.attrs), other.attrs);
+
+        if other.nested.is_some() {
+            self.nested = other.nested;
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn add_serde_attrs(&mut self, container: &Ident, formats: &mut Formats) {
+        self.attrs.extend(serde::rename(&self.ident, &self.name));
+
+        let optional = self.ty.is_optional();
+        if optional {
+            self.attrs.push(syn::parse_quote!(
+                #[serde(skip_serializing_if = "Option::is_none")]
+            ));
+        }
+
+        if self.flatten {
+            self.attrs.push(syn::parse_quote!(#[serde(flatten)]));
+        }
+
+        // The adapters tell `null` apart from an absent value themselves.
+        if let Some(attr) = formats.attr(&self.ty, optional) {
+            self.attrs.push(attr);
+        } else if self.ty.is_tri_state() {
+            self.attrs.push(serde::deserialize_nullable(container));
+        }
+    }
+
+    /// Whether the field needs the helper deserializing `Option<Option<T>>`s, rather than
+    /// adapters which do the same.
+    #[cfg(feature = "serde")]
+    fn is_nullable(&self) -> bool {
+        let mut leaves = BTreeSet::new();
+        serde::format_leaves(&self.ty, &mut leaves);
+        self.ty.is_tri_state() && leaves.is_empty()
     }
 }
 
@@ -45,9 +96,11 @@ impl From<&ast::Field> for Field {
         Self {
             ident: field.ident.clone().unwrap(),
             name: field.field_name(),
-            ty: TypeCodegen::Verbatim(field.ty.clone()),
+            ty: TypeCodegen::Verbatim(Box::new(field.ty.clone())),
             vis: field.vis.clone(),
             attrs: field.attrs.clone(),
+            flatten: false,
+            nested: field.type_name.clone(),
         }
     }
 }
@@ -55,17 +108,18 @@ impl From<&ast::Field> for Field {
 impl From<&FieldSchema> for Field {
     fn from(field: &FieldSchema) -> Self {
         Self {
-            ident: format_ident!("{}", field.name.to_snake_case()),
+            ident: field_ident(&field.name),
             name: field.name.clone(),
-            ty: if field.required {
-                TypeCodegen::from(&field.ty)
-            } else {
-                TypeCodegen::Optional(Box::new(TypeCodegen::from(&field.ty)))
-            },
+            ty: TypeCodegen::from(field),
             vis: Visibility::Public(VisPublic {
                 pub_token: Default::default(),
             }),
-            attrs: vec![],
+            attrs: docs::doc_attrs(&field.docs),
+            flatten: field.flatten,
+            nested: field
+                .ty
+                .nested()
+                .map(|nested| format_ident!("{}", nested.name)),
         }
     }
 }
@@ -76,15 +130,24 @@ pub struct Struct {
     pub attrs: Vec<Attribute>,
     pub fields: Vec<Field>,
     pub vis: Visibility,
+    /// Types generated for schemas declared inline in the fields.
+    pub nested: Vec<Nested>,
 }
 
 impl Struct {
-    pub fn combined(container: ast::StructContainer, schema: StructSchema) -> Result<Self, Error> {
+    /// Generates the struct for `schema`, declared as `container`. Any serde adapters its fields
+    /// need are added to `formats`.
+    pub fn combined(
+        container: ast::StructContainer,
+        schema: StructSchema,
+        formats: &mut Formats,
+    ) -> Result<Self, Error> {
         let mut strct = Self {
             ident: container.ident,
             vis: container.vis,
-            attrs: container.attrs,
+            attrs: docs::merge_attrs(docs::doc_attrs(&schema.docs), container.attrs),
             fields: schema.fields.iter().map(Field::from).collect(),
+            nested: vec![],
         };
 
         for field in &container.fields {
@@ -95,18 +158,51 @@ impl Struct {
             }
         }
 
-        strct.fields.sort_by_key(|f| f.ident.to_string());
+        let mut idents = HashSet::new();
+        if let Some(field) = strct.fields.iter().find(|f| !idents.insert(&f.ident)) {
+            return Err(Error::DuplicateField(field.ident.to_string()));
+        }
+
+        // The fields are still in the same order as the schema's at this point.
+        for (field, field_schema) in strct.fields.iter().zip(&schema.fields) {
+            if let (Some(ident), Some(nested)) = (&field.nested, field_schema.ty.nested()) {
+                strct.nested.push(Nested::new(
+                    ident.clone(),
+                    strct.vis.clone(),
+                    &strct.attrs,
+                    nested,
+                    formats,
+                )?);
+            }
+        }
+
+        if schema.order == Order::Alphabetical {
+            strct.fields.sort_by_key(|f| f.ident.unraw().to_string());
+        }
+
+        #[cfg(feature = "serde")]
+        strct.add_serde_attrs(schema.deny_unknown_fields, formats);
 
         Ok(strct)
     }
 
-    fn get_mut_field(&mut self, value: &str) -> Option<&mut Field> {
+    #[cfg(feature = "serde")]
+    fn add_serde_attrs(&mut self, deny_unknown_fields: bool, formats: &mut Formats) {
+        serde::add_derives(&mut self.attrs);
+
+        // Serde doesn't support denying unknown fields alongside flattened ones.
+        if deny_unknown_fields && !self.fields.iter().any(|field| field.flatten) {
+            self.attrs
+                .push(syn::parse_quote!(#[serde(deny_unknown_fields)]));
+        }
+
         for field in &mut self.fields {
-            if field.name == value {
-                return Some(field);
-            }
+            field.add_serde_attrs(&self.ident, formats);
         }
-        None
+    }
+
+    fn get_mut_field(&mut self, value: &str) -> Option<&mut Field> {
+        self.fields.iter_mut().find(|field| field.name == value)
     }
 
     pub fn definition_tokens(&self) -> TokenStream {
@@ -138,12 +234,22 @@ impl From<StructContainer> for Struct {
             fields: container.fields.iter().map(Field::from).collect(),
             vis: container.vis,
             attrs: container.attrs,
+            nested: vec![],
         }
     }
 }
 
 impl ToTokens for Struct {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        self.definition_to_tokens(tokens)
+        self.definition_to_tokens(tokens);
+
+        #[cfg(feature = "serde")]
+        if self.fields.iter().any(Field::is_nullable) {
+            serde::nullable_helper(&self.ident).to_tokens(tokens);
+        }
+
+        for nested in &self.nested {
+            nested.to_tokens(tokens);
+        }
     }
 }
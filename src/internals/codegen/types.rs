@@ -1,61 +1,189 @@
-use crate::internals::schema::TypeSchema;
+use crate::internals::schema::{FieldSchema, TypeSchema};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, ToTokens};
-use syn::Type;
+use syn::{Ident, Path, Type};
 
 #[derive(Debug)]
 pub enum TypeCodegen {
+    Int8,
+    Int16,
     Int32,
     Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
     Float,
     Double,
     String,
     Bool,
     Date,
     DateTime,
+    Time,
+    Duration,
+    Bytes,
+    Binary,
+    Uuid,
+    Url,
+    Ipv4,
+    Ipv6,
     Array(Box<TypeCodegen>),
     Set(Box<TypeCodegen>),
-    Object(String),
+    Object(Path),
     Optional(Box<TypeCodegen>),
-    Verbatim(Type),
+    Nullable(Box<TypeCodegen>),
+    Map(Box<TypeCodegen>),
+    OrderedMap(Box<TypeCodegen>),
+    /// Any JSON value, as the type the user has given or `serde_json::Value`.
+    Any(Option<Path>),
+    Nested(Ident),
+    /// A component generated as a type alias, along with the type it stands for.
+    #[cfg_attr(not(feature = "serde"), allow(unused))]
+    Alias(Path, Box<TypeCodegen>),
+    Verbatim(Box<Type>),
 }
 
 impl TypeCodegen {
-    #[cfg(feature = "chrono")]
-    fn date_tokens() -> TokenStream {
-        quote! {chrono::NaiveData}
+    /// Renames the type generated for a schema declared inline in this type.
+    pub fn set_nested(&mut self, ident: Ident) {
+        match self {
+            Self::Nested(nested) => *nested = ident,
+            Self::Array(ty)
+            | Self::Set(ty)
+            | Self::Optional(ty)
+            | Self::Nullable(ty)
+            | Self::Map(ty)
+            | Self::OrderedMap(ty) => ty.set_nested(ident),
+            _ => {}
+        }
+    }
+
+    /// Whether the type is an `Option`, either generated or written by the user.
+    #[cfg(feature = "serde")]
+    pub fn is_optional(&self) -> bool {
+        match self {
+            Self::Optional(_) => true,
+            Self::Verbatim(ty) => match ty.as_ref() {
+                Type::Path(path) => path
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|segment| segment.ident == "Option"),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether the type is an optional field which distinguishes being absent from being `null`.
+    #[cfg(feature = "serde")]
+    pub fn is_tri_state(&self) -> bool {
+        matches!(self, Self::Optional(ty) if matches!(ty.as_ref(), Self::Nullable(_)))
     }
 
-    #[cfg(not(feature = "chrono"))]
+    // Dates and times are strings unless the `chrono` or `time` feature is enabled, with `chrono`
+    // taking precedence if both are.
+
     fn date_tokens() -> TokenStream {
-        quote! {String}
+        if cfg!(feature = "chrono") {
+            quote! {chrono::NaiveDate}
+        } else if cfg!(feature = "time") {
+            quote! {time::Date}
+        } else {
+            quote! {String}
+        }
     }
 
-    #[cfg(feature = "chrono")]
     fn datetime_tokens() -> TokenStream {
-        quote! {chrono::DateTime<Utc>}
+        if cfg!(feature = "chrono") {
+            quote! {chrono::DateTime<chrono::Utc>}
+        } else if cfg!(feature = "time") {
+            quote! {time::OffsetDateTime}
+        } else {
+            quote! {String}
+        }
     }
 
-    #[cfg(not(feature = "chrono"))]
-    fn datetime_tokens() -> TokenStream {
-        quote! {String}
+    fn time_tokens() -> TokenStream {
+        if cfg!(feature = "chrono") {
+            quote! {chrono::NaiveTime}
+        } else if cfg!(feature = "time") {
+            quote! {time::Time}
+        } else {
+            quote! {String}
+        }
+    }
+
+    fn duration_tokens() -> TokenStream {
+        if cfg!(feature = "chrono") {
+            quote! {chrono::Duration}
+        } else if cfg!(feature = "time") {
+            quote! {time::Duration}
+        } else {
+            quote! {String}
+        }
+    }
+
+    /// Binary data is held in a `bytes::Bytes` if the `bytes` feature is enabled.
+    fn binary_tokens() -> TokenStream {
+        if cfg!(feature = "bytes") {
+            quote! {bytes::Bytes}
+        } else {
+            quote! {Vec<u8>}
+        }
     }
 }
 
 impl From<&TypeSchema> for TypeCodegen {
     fn from(typ: &TypeSchema) -> Self {
         match typ {
+            TypeSchema::Int8 => Self::Int8,
+            TypeSchema::Int16 => Self::Int16,
             TypeSchema::Int32 => Self::Int32,
             TypeSchema::Int64 => Self::Int64,
+            TypeSchema::UInt8 => Self::UInt8,
+            TypeSchema::UInt16 => Self::UInt16,
+            TypeSchema::UInt32 => Self::UInt32,
+            TypeSchema::UInt64 => Self::UInt64,
             TypeSchema::Float => Self::Float,
             TypeSchema::Double => Self::Double,
             TypeSchema::String => Self::String,
             TypeSchema::Bool => Self::Bool,
             TypeSchema::Date => Self::Date,
             TypeSchema::DateTime => Self::DateTime,
+            TypeSchema::Time => Self::Time,
+            TypeSchema::Duration => Self::Duration,
+            TypeSchema::Bytes => Self::Bytes,
+            TypeSchema::Binary => Self::Binary,
+            TypeSchema::Uuid => Self::Uuid,
+            TypeSchema::Url => Self::Url,
+            TypeSchema::Ipv4 => Self::Ipv4,
+            TypeSchema::Ipv6 => Self::Ipv6,
+            TypeSchema::Custom(path) => Self::Object(path.clone()),
             TypeSchema::Array(t) => Self::Array(Box::new(Self::from(t.as_ref()))),
             TypeSchema::Set(t) => Self::Set(Box::new(Self::from(t.as_ref()))),
-            TypeSchema::Object(reference) => Self::Object(reference.to_owned()),
+            TypeSchema::Object(path) => Self::Object(path.clone()),
+            TypeSchema::Nullable(t) => Self::Nullable(Box::new(Self::from(t.as_ref()))),
+            TypeSchema::Map(t) => Self::Map(Box::new(Self::from(t.as_ref()))),
+            TypeSchema::OrderedMap(t) => Self::OrderedMap(Box::new(Self::from(t.as_ref()))),
+            TypeSchema::Any(path) => Self::Any(path.clone()),
+            TypeSchema::Nested(nested) => Self::Nested(format_ident!("{}", nested.name)),
+            TypeSchema::Alias(path, t) => {
+                Self::Alias(path.clone(), Box::new(Self::from(t.as_ref())))
+            }
+        }
+    }
+}
+
+impl From<&FieldSchema> for TypeCodegen {
+    fn from(field: &FieldSchema) -> Self {
+        match &field.ty {
+            _ if field.required => Self::from(&field.ty),
+            // A single `Option` covers both absent and `null` unless they need to be told apart.
+            TypeSchema::Nullable(ty) if !field.tri_state => {
+                Self::Optional(Box::new(Self::from(ty.as_ref())))
+            }
+            ty => Self::Optional(Box::new(Self::from(ty))),
         }
     }
 }
@@ -63,18 +191,39 @@ impl From<&TypeSchema> for TypeCodegen {
 impl ToTokens for TypeCodegen {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match self {
+            Self::Int8 => quote! {i8},
+            Self::Int16 => quote! {i16},
             Self::Int32 => quote! {i32},
             Self::Int64 => quote! {i64},
+            Self::UInt8 => quote! {u8},
+            Self::UInt16 => quote! {u16},
+            Self::UInt32 => quote! {u32},
+            Self::UInt64 => quote! {u64},
             Self::Float => quote! {f32},
             Self::Double => quote! {f64},
             Self::String => quote! {String},
             Self::Bool => quote! {bool},
             Self::Date => Self::date_tokens(),
             Self::DateTime => Self::datetime_tokens(),
+            Self::Time => Self::time_tokens(),
+            Self::Duration => Self::duration_tokens(),
+            Self::Bytes => quote! {Vec<u8>},
+            Self::Binary => Self::binary_tokens(),
+            Self::Uuid => quote! {uuid::Uuid},
+            Self::Url => quote! {url::Url},
+            Self::Ipv4 => quote! {std::net::Ipv4Addr},
+            Self::Ipv6 => quote! {std::net::Ipv6Addr},
             Self::Array(t) => quote! {Vec<#t>},
             Self::Set(t) => quote! {std::collections::HashSet<#t>},
-            Self::Object(reference) => format_ident!("{}", reference).into_token_stream(),
+            Self::Object(path) => path.to_token_stream(),
             Self::Optional(t) => quote! {Option<#t>},
+            Self::Nullable(t) => quote! {Option<#t>},
+            Self::Map(t) => quote! {std::collections::HashMap<String, #t>},
+            Self::OrderedMap(t) => quote! {std::collections::BTreeMap<String, #t>},
+            Self::Any(Some(path)) => path.to_token_stream(),
+            Self::Any(None) => quote! {serde_json::Value},
+            Self::Nested(ident) => ident.to_token_stream(),
+            Self::Alias(path, _) => path.to_token_stream(),
             Self::Verbatim(t) => t.to_token_stream(),
         }
         .to_tokens(tokens)
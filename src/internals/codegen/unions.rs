@@ -0,0 +1,202 @@
+use crate::internals::ast::EnumContainer;
+use crate::internals::attrs::Order;
+use crate::internals::codegen::docs;
+use crate::internals::codegen::formats::Formats;
+use crate::internals::codegen::idents::variant_ident;
+use crate::internals::codegen::nested::Nested;
+#[cfg(feature = "serde")]
+use crate::internals::codegen::serde;
+use crate::internals::codegen::types::TypeCodegen;
+use crate::internals::schema::{UnionSchema, UnionVariantSchema};
+use crate::internals::{ast, Error};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use std::collections::HashSet;
+use syn::{Attribute, Ident, Visibility};
+
+#[derive(Debug)]
+pub struct UnionVariant {
+    name: Ident,
+    attrs: Vec<Attribute>,
+    value: String,
+    ty: TypeCodegen,
+    #[cfg_attr(not(feature = "serde"), allow(unused))]
+    declares_tag: bool,
+}
+
+impl UnionVariant {
+    /// Takes the name and attributes of a variant declared by the user. The type always comes
+    /// from the schema, as declared variants are unit variants.
+    pub fn update(&mut self, other: &ast::Variant) {
+        self.name = other.ident.clone();
+        self.attrs = docs::merge_attrs(std::mem::take(&mut self.attrs), other.attrs.clone());
+    }
+}
+
+impl From<&UnionVariantSchema> for UnionVariant {
+    fn from(variant: &UnionVariantSchema) -> Self {
+        Self {
+            name: variant_ident(&variant.value),
+            attrs: docs::doc_attrs(&variant.docs),
+            value: variant.value.clone(),
+            ty: TypeCodegen::from(&variant.ty),
+            declares_tag: variant.declares_tag,
+        }
+    }
+}
+
+impl ToTokens for UnionVariant {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.name;
+        let attrs = &self.attrs;
+        let ty = &self.ty;
+        (quote! {
+            #(#attrs)*
+            #name(#ty)
+        })
+        .to_tokens(tokens);
+    }
+}
+
+/// An enum generated from a `oneOf` schema, with a tuple variant wrapping each alternative.
+#[derive(Debug)]
+pub struct Union {
+    name: Ident,
+    variants: Vec<UnionVariant>,
+    vis: Visibility,
+    attrs: Vec<Attribute>,
+    tag: Option<String>,
+    /// Types generated for alternatives declared inline.
+    nested: Vec<Nested>,
+}
+
+impl Union {
+    /// Generates the enum for `schema`, declared as `container`. Any serde adapters its variants
+    /// need are added to `formats`.
+    pub fn combined(
+        container: EnumContainer,
+        schema: UnionSchema,
+        formats: &mut Formats,
+    ) -> Result<Self, Error> {
+        let mut union = Self {
+            name: container.ident,
+            variants: schema.variants.iter().map(UnionVariant::from).collect(),
+            vis: container.vis,
+            attrs: docs::merge_attrs(docs::doc_attrs(&schema.docs), container.attrs),
+            tag: schema.tag,
+            nested: vec![],
+        };
+
+        for variant in &schema.variants {
+            if let Some(nested) = variant.ty.nested() {
+                union.nested.push(Nested::new(
+                    format_ident!("{}", nested.name),
+                    union.vis.clone(),
+                    &union.attrs,
+                    nested,
+                    formats,
+                )?);
+            }
+        }
+
+        for variant in &container.variants {
+            if let Some(v) = union.get_mut_variant(&variant.value()) {
+                v.update(variant)
+            } else {
+                return Err(Error::InvalidEnumVariant(variant.value()));
+            }
+        }
+
+        let mut names = HashSet::new();
+        if let Some(variant) = union.variants.iter().find(|v| !names.insert(&v.name)) {
+            return Err(Error::DuplicateVariant(variant.name.to_string()));
+        }
+
+        // Untagged alternatives are kept in the order the schema chose, which serde relies on.
+        if union.tag.is_some() && schema.order == Order::Alphabetical {
+            union.variants.sort_by_key(|v| v.name.to_string());
+        }
+
+        #[cfg(feature = "serde")]
+        union.add_serde_attrs(formats);
+
+        Ok(union)
+    }
+
+    /// Alternatives with a discriminator are internally tagged, otherwise each alternative is
+    /// tried in turn. Unions whose alternatives declare the tag themselves are serialized by the
+    /// impls emitted alongside the enum instead, as serde would remove the tag before handing the
+    /// object to the alternative and add it again when serializing.
+    #[cfg(feature = "serde")]
+    fn add_serde_attrs(&mut self, formats: &mut Formats) {
+        if self.declared_tag().is_some() {
+            return;
+        }
+
+        serde::add_derives(&mut self.attrs);
+
+        match &self.tag {
+            Some(tag) => {
+                self.attrs.push(syn::parse_quote!(#[serde(tag = #tag)]));
+
+                for variant in &mut self.variants {
+                    variant
+                        .attrs
+                        .extend(serde::rename(&variant.name, &variant.value));
+                }
+            }
+            None => {
+                self.attrs.push(syn::parse_quote!(#[serde(untagged)]));
+
+                for variant in &mut self.variants {
+                    variant.attrs.extend(formats.attr(&variant.ty, false));
+                }
+            }
+        }
+    }
+
+    /// The tag of a union with alternatives which declare it themselves.
+    #[cfg(feature = "serde")]
+    fn declared_tag(&self) -> Option<&str> {
+        self.tag
+            .as_deref()
+            .filter(|_| self.variants.iter().any(|variant| variant.declares_tag))
+    }
+
+    fn get_mut_variant(&mut self, value: &str) -> Option<&mut UnionVariant> {
+        self.variants
+            .iter_mut()
+            .find(|variant| variant.value == value)
+    }
+}
+
+impl ToTokens for Union {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.name;
+        let variants = &self.variants;
+        let vis = &self.vis;
+        let attrs = &self.attrs;
+
+        (quote! {
+            #(#attrs)*
+            #vis enum #name {
+                #(#variants),*
+            }
+        })
+        .to_tokens(tokens);
+
+        #[cfg(feature = "serde")]
+        if let Some(tag) = self.declared_tag() {
+            let variants: Vec<_> = self
+                .variants
+                .iter()
+                .map(|variant| (&variant.name, variant.value.as_str(), variant.declares_tag))
+                .collect();
+            serde::tagged_union_impls(name, tag, &variants).to_tokens(tokens);
+        }
+
+        for nested in &self.nested {
+            nested.to_tokens(tokens);
+        }
+    }
+}
@@ -5,18 +5,33 @@ use syn::Error as SynError;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum Error {
-    CheckFailed(CheckFailure),
+    CheckFailed(Vec<Located<CheckFailure>>),
+    CircularReference(String),
+    ConflictingFieldTypes(String),
     Darling(String),
+    DuplicateField(String),
+    DuplicateVariant(String),
     EnumNotDefined,
-    FileOpenFailed(String),
+    FileOpenFailed(String, String),
+    FileParseFailed(String, String),
     FileReadFailed(String),
     InvalidEnumVariant(String),
+    InvalidReference(String),
+    InvalidRustType(String),
     InvalidStructField(String),
+    InvalidTypeName(String),
+    MismatchedCatchAll(String, String),
+    MismatchedStubKind(String),
     MissingComponents,
+    MissingEnvVar(String),
     ModelNotFound(String),
+    ModuleNotInline,
     UnknownField(String),
     UnsupportedEnumType,
+    UnsupportedInlineVariant,
+    UnsupportedModuleArgument(String),
     UnsupportedNestedObjectType,
+    UnsupportedReference(String),
     UnsupportedSchemaType(String),
     UnsupportedStructType,
 }
@@ -27,27 +42,86 @@ impl From<darling::Error> for Error {
     }
 }
 
-impl Into<SynError> for Error {
-    fn into(self) -> SynError {
-        SynError::new(Span::call_site(), self.to_string())
+impl From<Error> for SynError {
+    fn from(err: Error) -> Self {
+        match err {
+            // Every failure is reported at once, each pointing at the code it concerns.
+            Error::CheckFailed(failures) => failures
+                .into_iter()
+                .map(|failure| {
+                    SynError::new(
+                        failure.span,
+                        format!("schema check failed due to {}", failure.value),
+                    )
+                })
+                .reduce(|mut combined, err| {
+                    combined.combine(err);
+                    combined
+                })
+                .unwrap_or_else(|| SynError::new(Span::call_site(), "schema check failed")),
+            err => SynError::new(Span::call_site(), err.to_string()),
+        }
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Error::CheckFailed(cf) => format!("schema check failed due to {}", cf).fmt(f),
+            Error::CheckFailed(cfs) => {
+                let cfs: Vec<_> = cfs.iter().map(|cf| cf.value.to_string()).collect();
+                format!("schema check failed due to {}", cfs.join(", ")).fmt(f)
+            }
+            Error::CircularReference(r) => format!("circular reference to '{}'", r).fmt(f),
+            Error::ConflictingFieldTypes(fi) => {
+                format!("field '{}' is declared with conflicting types", fi).fmt(f)
+            }
             Error::Darling(err) => err.fmt(f),
+            Error::DuplicateField(v) => format!("more than one field is called '{}'", v).fmt(f),
+            Error::DuplicateVariant(v) => format!("more than one variant is called '{}'", v).fmt(f),
             Error::EnumNotDefined => "enum not defined".fmt(f),
-            Error::FileOpenFailed(fs) => format!("failed to open file '{}'", fs).fmt(f),
+            Error::FileOpenFailed(fs, err) => {
+                format!("failed to open file '{}': {}", fs, err).fmt(f)
+            }
+            Error::FileParseFailed(fs, err) => {
+                format!("failed to parse file '{}': {}", fs, err).fmt(f)
+            }
             Error::FileReadFailed(fs) => format!("failed to read file '{}'", fs).fmt(f),
             Error::InvalidEnumVariant(v) => format!("unknown variant '{}'", v).fmt(f),
+            Error::InvalidReference(r) => format!("invalid reference '{}'", r).fmt(f),
+            Error::InvalidRustType(t) => format!("'{}' is not a valid Rust type path", t).fmt(f),
             Error::InvalidStructField(fi) => format!("unknown field '{}'", fi).fmt(f),
+            Error::InvalidTypeName(n) => format!("'{}' is not a valid type name", n).fmt(f),
+            Error::MismatchedCatchAll(v, expected) => format!(
+                "'{}' must be declared as '{}' to hold the values the schema doesn't list",
+                v, expected
+            )
+            .fmt(f),
+            Error::MismatchedStubKind(n) => format!(
+                "'{}' is declared as a different kind of type than its schema generates",
+                n
+            )
+            .fmt(f),
             Error::MissingComponents => "schema missing components structure".fmt(f),
+            Error::MissingEnvVar(v) => format!("environment variable '{}' is not set", v).fmt(f),
             Error::ModelNotFound(m) => format!("model '{}' not found in schemas", m).fmt(f),
+            Error::ModuleNotInline => "modules must be declared inline".fmt(f),
             Error::UnknownField(n) => format!("unknown field '{}'", n).fmt(f),
-            Error::UnsupportedEnumType => "enums are only supported for string types".fmt(f),
+            Error::UnsupportedEnumType => {
+                "enums are only supported for string and integer enumerations and oneOf types"
+                    .fmt(f)
+            }
+            Error::UnsupportedInlineVariant => {
+                "oneOf alternatives must be references when a discriminator is used".fmt(f)
+            }
+            Error::UnsupportedModuleArgument(a) => {
+                format!("'{}' is not supported when generating every component", a).fmt(f)
+            }
             Error::UnsupportedNestedObjectType => "nested objects are not supported".fmt(f),
+            Error::UnsupportedReference(r) => format!(
+                "reference '{}' is not supported, only '#/components/schemas/...' is",
+                r
+            )
+            .fmt(f),
             Error::UnsupportedSchemaType(s) => format!("'{}' is not supported", s).fmt(f),
             Error::UnsupportedStructType => "structs are only supported for object types".fmt(f),
         }
@@ -56,25 +130,57 @@ impl fmt::Display for Error {
 
 impl StdError for Error {}
 
+/// A value along with the span it should be reported at. Spans can't be compared, so only the
+/// values are considered for equality.
+#[derive(Debug)]
+pub struct Located<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+impl<T> Located<T> {
+    pub fn new(value: T, span: Span) -> Self {
+        Self { value, span }
+    }
+}
+
+impl<T: PartialEq> PartialEq for Located<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Located<T> {}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum CheckFailure {
-    MissingFields(Vec<String>),
-    MissingVariants(Vec<String>),
-    UnknownFields(Vec<String>),
-    UnknownVariants(Vec<String>),
+    MismatchedType {
+        field: String,
+        expected: String,
+        found: String,
+    },
+    MissingField(String),
+    MissingVariant(String),
+    UnknownField(String),
+    UnknownVariant(String),
 }
 
 impl fmt::Display for CheckFailure {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            CheckFailure::MissingFields(fs) => format!("missing fields: {}", fs.join(", ")).fmt(f),
-            CheckFailure::MissingVariants(vs) => {
-                format!("missing variants: {}", vs.join(", ")).fmt(f)
-            }
-            CheckFailure::UnknownFields(fs) => format!("unknown fields: {}", fs.join(", ")).fmt(f),
-            CheckFailure::UnknownVariants(vs) => {
-                format!("unknown variants: {}", vs.join(", ")).fmt(f)
-            }
+            CheckFailure::MismatchedType {
+                field,
+                expected,
+                found,
+            } => format!(
+                "mismatched type for field '{}': expected `{}`, found `{}`",
+                field, expected, found
+            )
+            .fmt(f),
+            CheckFailure::MissingField(fi) => format!("missing field '{}'", fi).fmt(f),
+            CheckFailure::MissingVariant(v) => format!("missing variant '{}'", v).fmt(f),
+            CheckFailure::UnknownField(fi) => format!("unknown field '{}'", fi).fmt(f),
+            CheckFailure::UnknownVariant(v) => format!("unknown variant '{}'", v).fmt(f),
         }
     }
 }
@@ -1,68 +1,193 @@
 use crate::internals::ast::{Container, EnumContainer, StructContainer};
 use crate::internals::attrs::OpenApiArgs;
-use crate::internals::codegen::{Enum, Struct};
+use crate::internals::codegen::{Enum, Formats, Struct, Union};
 use crate::internals::error::Error;
-use crate::internals::utils::openapi_from_file;
+use crate::internals::resolver::Resolver;
+use crate::internals::schema::{EnumSchema, StructSchema, TryFromSchema, UnionSchema};
+use crate::internals::spec::Spec;
 use darling::ast::Data;
 use darling::{FromDeriveInput, FromMeta};
-use openapiv3::{ReferenceOr, Schema};
+use openapiv3::SchemaKind;
 use proc_macro2::TokenStream;
 use quote::ToTokens;
-use std::convert::TryInto;
 use syn::{AttributeArgs, DeriveInput};
 
 pub fn from_openapi(ast: &DeriveInput, attrs: &AttributeArgs) -> Result<TokenStream, Error> {
     let container: Container = Container::from_derive_input(ast)?;
     let args: OpenApiArgs = OpenApiArgs::from_list(attrs)?;
 
-    match &container.data {
-        Data::Enum(_) => Ok(from_openapi_enum(container.into(), args)?),
-        Data::Struct(_) => Ok(from_openapi_struct(container.into(), args)?),
-    }
-}
+    let spec = Spec::load(&args.schema)?;
+    let resolver = Resolver::new(&spec, &args);
+
+    let mut tokens = match &container.data {
+        Data::Enum(_) => from_openapi_enum(container.into(), &args, &resolver)?,
+        Data::Struct(_) => from_openapi_struct(container.into(), &args, &resolver)?,
+    };
+    tokens.extend(spec.dependency_tokens());
 
-fn from_openapi_enum(container: EnumContainer, args: OpenApiArgs) -> Result<TokenStream, Error> {
-    let schema = openapi_from_file(&args.schema)?;
-    let components = schema.components.ok_or(Error::MissingComponents)?;
+    Ok(tokens)
+}
 
+fn from_openapi_enum(
+    container: EnumContainer,
+    args: &OpenApiArgs,
+    resolver: &Resolver,
+) -> Result<TokenStream, Error> {
     let reference = args
         .reference
+        .clone()
         .unwrap_or_else(|| container.ident.to_string());
+    let (resolver, component) = resolver.component(&reference)?;
 
-    let component: &Schema = match components.schemas.get(&reference) {
-        Some(ReferenceOr::Item(schema)) => schema,
-        _ => return Err(Error::ModelNotFound(reference).into()),
-    };
-
-    Ok(Enum::combined(container, component.try_into()?)?.into_token_stream())
+    match &component.schema_kind {
+        SchemaKind::OneOf { .. } | SchemaKind::AnyOf { .. } => {
+            let scope = container.ident.to_string();
+            let schema = UnionSchema::try_from_schema(component, &resolver.scoped(&scope))?;
+            let mut formats = Formats::for_type(&container.ident);
+            let mut tokens = Union::combined(container, schema, &mut formats)?.into_token_stream();
+            formats.to_tokens(&mut tokens);
+            Ok(tokens)
+        }
+        _ => {
+            let schema = EnumSchema::try_from_schema(component, &resolver)?;
+            Ok(Enum::combined(container, schema)?.into_token_stream())
+        }
+    }
 }
 
 fn from_openapi_struct(
     container: StructContainer,
-    args: OpenApiArgs,
+    args: &OpenApiArgs,
+    resolver: &Resolver,
 ) -> Result<TokenStream, Error> {
-    let schema = openapi_from_file(&args.schema)?;
-    let components = schema.components.ok_or(Error::MissingComponents)?;
-
     let reference = args
         .reference
+        .clone()
         .unwrap_or_else(|| container.ident.to_string());
+    let (resolver, component) = resolver.component(&reference)?;
+    let scope = container.ident.to_string();
+    let schema = StructSchema::try_from_schema(component, &resolver.scoped(&scope))?;
+    let mut formats = Formats::for_type(&container.ident);
+    let mut tokens = Struct::combined(container, schema, &mut formats)?.into_token_stream();
+    formats.to_tokens(&mut tokens);
 
-    let component: &Schema = match components.schemas.get(&reference) {
-        Some(ReferenceOr::Item(schema)) => schema,
-        _ => return Err(Error::ModelNotFound(reference).into()),
-    };
-
-    Ok(Struct::combined(container, component.try_into()?)?.into_token_stream())
+    Ok(tokens)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::internals::from_openapi::from_openapi;
-    use crate::internals::utils::parse2;
+    use crate::internals::spec::Spec;
+    use crate::internals::utils::{parse2, without_serde};
+    use crate::internals::Error;
+    use proc_macro2::TokenStream;
     use quote::quote;
     use syn::{AttributeArgs, DeriveInput};
 
+    /// The impls generated alongside `TestEnum`.
+    fn test_enum_impls() -> TokenStream {
+        quote! {
+            impl TestEnum {
+                /// Every variant, in the order the schema lists them.
+                pub const ALL: &'static [Self] = &[Self::Foo, Self::Bar];
+
+                /// The value the variant represents in the schema.
+                pub fn as_str(&self) -> &'static str {
+                    match self {
+                        Self::Foo => "foo",
+                        Self::Bar => "bar",
+                    }
+                }
+            }
+
+            impl std::fmt::Display for TestEnum {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+
+            impl std::str::FromStr for TestEnum {
+                type Err = ParseTestEnumError;
+
+                fn from_str(value: &str) -> Result<Self, Self::Err> {
+                    match value {
+                        "foo" => Ok(Self::Foo),
+                        "bar" => Ok(Self::Bar),
+                        value => Err(ParseTestEnumError {
+                            value: value.to_string(),
+                        }),
+                    }
+                }
+            }
+
+            #[doc = "The error returned when parsing a string which isn't one of the values of [TestEnum]."]
+            #[derive(Clone, Debug, PartialEq, Eq)]
+            struct ParseTestEnumError {
+                value: String,
+            }
+
+            impl std::fmt::Display for ParseTestEnumError {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "unknown TestEnum value `{}`", self.value)
+                }
+            }
+
+            impl std::error::Error for ParseTestEnumError {}
+        }
+    }
+
+    /// The impls generated alongside `TestNestedSize`, which is declared with `vis`.
+    fn test_nested_size_impls(vis: TokenStream) -> TokenStream {
+        quote! {
+            impl TestNestedSize {
+                /// Every variant, in the order the schema lists them.
+                pub const ALL: &'static [Self] = &[Self::Small, Self::Large];
+
+                /// The value the variant represents in the schema.
+                pub fn as_str(&self) -> &'static str {
+                    match self {
+                        Self::Small => "small",
+                        Self::Large => "large",
+                    }
+                }
+            }
+
+            impl std::fmt::Display for TestNestedSize {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+
+            impl std::str::FromStr for TestNestedSize {
+                type Err = ParseTestNestedSizeError;
+
+                fn from_str(value: &str) -> Result<Self, Self::Err> {
+                    match value {
+                        "small" => Ok(Self::Small),
+                        "large" => Ok(Self::Large),
+                        value => Err(ParseTestNestedSizeError {
+                            value: value.to_string(),
+                        }),
+                    }
+                }
+            }
+
+            #[doc = "The error returned when parsing a string which isn't one of the values of [TestNestedSize]."]
+            #[derive(Clone, Debug, PartialEq, Eq)]
+            #vis struct ParseTestNestedSizeError {
+                value: String,
+            }
+
+            impl std::fmt::Display for ParseTestNestedSizeError {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "unknown TestNestedSize value `{}`", self.value)
+                }
+            }
+
+            impl std::error::Error for ParseTestNestedSizeError {}
+        }
+    }
+
     #[test]
     fn test_enum() {
         let args = quote! { schema = "./test-resources/test_schema.yaml" };
@@ -73,16 +198,51 @@ mod tests {
         let args: AttributeArgs = parse2(args).unwrap();
         let input: DeriveInput = parse2(input).unwrap();
 
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let impls = test_enum_impls();
         let expected = quote! {
             enum TestEnum {
-                Bar,
-                Foo
+                Foo,
+                Bar
             }
+            #impls
+            #dependencies
         };
 
         assert_eq!(
-            from_openapi(&input, &args).map(|s| s.to_string()),
-            Ok(expected.to_string())
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_enum_json() {
+        let args = quote! { schema = "./test-resources/test_schema.json" };
+        let input = quote! {
+            enum TestEnum {}
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.json")
+            .unwrap()
+            .dependency_tokens();
+        let impls = test_enum_impls();
+        let expected = quote! {
+            enum TestEnum {
+                Foo,
+                Bar
+            }
+            #impls
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
         );
     }
 
@@ -96,11 +256,1713 @@ mod tests {
         let args: AttributeArgs = parse2(args).unwrap();
         let input: DeriveInput = parse2(input).unwrap();
 
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
         let expected = quote! {
             struct TestStruct {
-                pub bar: Option<i32>,
-                pub foo: String
+                pub foo: String,
+                pub bar: Option<i32>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_docs() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestDocs;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            #[doc = " Documented"]
+            #[doc = ""]
+            #[doc = " An object with documentation."]
+            #[doc = " It spans two lines."]
+            #[doc = ""]
+            #[doc = " See [the guide](https://example.com/guide)."]
+            #[doc = ""]
+            #[doc = " # Example"]
+            #[doc = ""]
+            #[doc = " ```json"]
+            #[doc = " {"]
+            #[doc = "   \"name\": \"Rex\""]
+            #[doc = " }"]
+            #[doc = " ```"]
+            struct TestDocs {
+                #[doc = " The name of the object."]
+                pub name: Option<String>,
+                #[deprecated]
+                pub legacy: Option<i32>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_docs_declared() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            /// Written by hand.
+            struct TestDocs {
+                /// Also written by hand.
+                name: Option<String>,
+                #[deprecated = "use `name`"]
+                legacy: Option<i32>,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            /// Written by hand.
+            struct TestDocs {
+                /// Also written by hand.
+                name: Option<String>,
+                #[deprecated = "use `name`"]
+                legacy: Option<i32>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_enum_docs() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            enum TestDocsKind {}
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let output = without_serde(from_openapi(&input, &args).unwrap());
+        let expected = quote! {
+            #[doc = " The kind of an object."]
+            #[deprecated]
+            enum TestDocsKind {
+                Plain
+            }
+        };
+
+        assert!(output.starts_with(&without_serde(expected)));
+    }
+
+    #[cfg(not(any(feature = "chrono", feature = "time")))]
+    #[test]
+    fn test_struct_temporal() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestTemporal;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestTemporal {
+                pub date: String,
+                pub timestamp: String,
+                pub time: String,
+                pub timeout: String,
+                pub delay: Option<String>,
+                pub history: Option<Vec<String> >
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[cfg(not(feature = "bytes"))]
+    #[test]
+    fn test_struct_binary() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestBinary;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestBinary {
+                pub signature: Vec<u8>,
+                pub file: Vec<u8>,
+                pub thumbnail: Option<Vec<u8> >
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[cfg(not(any(feature = "uuid", feature = "url")))]
+    #[test]
+    fn test_struct_formats() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestFormats;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let (address, address_v6) = if cfg!(feature = "ip") {
+            (quote! {std::net::Ipv4Addr}, quote! {std::net::Ipv6Addr})
+        } else {
+            (quote! {String}, quote! {String})
+        };
+        let expected = quote! {
+            struct TestFormats {
+                pub id: String,
+                pub homepage: Option<String>,
+                pub email: Option<String>,
+                pub host: Option<String>,
+                pub address: #address,
+                pub address_v6: Option<#address_v6>,
+                pub phone: Option<String>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_formats_table() {
+        let args = quote! {
+            schema = "./test-resources/test_schema.yaml",
+            formats(uuid = "crate::Id", email = "crate::Email", phone_number = "crate::Phone")
+        };
+        let input = quote! {
+            struct TestFormats;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let homepage = if cfg!(feature = "url") {
+            quote! {url::Url}
+        } else {
+            quote! {String}
+        };
+        let (address, address_v6) = if cfg!(feature = "ip") {
+            (quote! {std::net::Ipv4Addr}, quote! {std::net::Ipv6Addr})
+        } else {
+            (quote! {String}, quote! {String})
+        };
+        let expected = quote! {
+            struct TestFormats {
+                pub id: crate::Id,
+                pub homepage: Option<#homepage>,
+                pub email: Option<crate::Email>,
+                pub host: Option<String>,
+                pub address: #address,
+                pub address_v6: Option<#address_v6>,
+                pub phone: Option<crate::Phone>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_rust_types() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestRustTypes;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestRustTypes {
+                pub price: crate::Decimal,
+                pub count: u64,
+                pub tags: Option<Vec<crate::Tag> >,
+                pub ratio: Option<crate::Decimal>,
+                pub total: Option<i64>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_rust_types_all_of() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestRustTypesDerived;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestRustTypesDerived {
+                pub price: crate::Decimal,
+                pub count: u64,
+                pub tags: Option<Vec<crate::Tag> >,
+                pub ratio: Option<crate::Decimal>,
+                pub total: Option<i64>,
+                pub discount: Option<crate::Decimal>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_type_map() {
+        let args = quote! {
+            schema = "./test-resources/test_schema.yaml",
+            type_map(TestMoney = "crate::Money", int64 = "crate::Int")
+        };
+        let input = quote! {
+            struct TestRustTypes;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestRustTypes {
+                pub price: crate::Money,
+                pub count: u64,
+                pub tags: Option<Vec<crate::Tag> >,
+                pub ratio: Option<crate::Decimal>,
+                pub total: Option<crate::Int>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_type_precedence() {
+        let args = quote! {
+            schema = "./test-resources/test_schema.yaml",
+            type_map(email = "crate::Mapped"),
+            formats(email = "crate::Format", hostname = "crate::Host")
+        };
+        let input = quote! {
+            struct TestTypePrecedence;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestTypePrecedence {
+                pub extension: Option<crate::Extension>,
+                pub mapped: Option<crate::Mapped>,
+                pub formatted: Option<crate::Host>,
+                pub built_in: Option<i64>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_integers() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestIntegers;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestIntegers {
+                pub tiny: i8,
+                pub small: i16,
+                pub byte: u8,
+                pub count: u32,
+                pub total: u64,
+                pub percent: i32,
+                pub port: i32,
+                pub limit: i32,
+                pub size: i32,
+                pub offset: i64
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_infer_unsigned() {
+        let args = quote! {
+            schema = "./test-resources/test_schema.yaml",
+            infer_unsigned
+        };
+        let input = quote! {
+            struct TestIntegers;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestIntegers {
+                pub tiny: i8,
+                pub small: i16,
+                pub byte: u8,
+                pub count: u32,
+                pub total: u64,
+                pub percent: u8,
+                pub port: u16,
+                pub limit: u8,
+                pub size: u32,
+                pub offset: u64
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_struct_temporal_chrono() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestTemporal;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestTemporal {
+                pub date: chrono::NaiveDate,
+                pub timestamp: chrono::DateTime<chrono::Utc>,
+                pub time: chrono::NaiveTime,
+                pub timeout: chrono::Duration,
+                pub delay: Option<chrono::Duration>,
+                pub history: Option<Vec<chrono::DateTime<chrono::Utc> > >
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_references() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestReferences;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestReferences {
+                pub test_struct: TestStruct,
+                pub test_enums: Option<Vec<TestEnum> >
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_references_renamed() {
+        let args = quote! {
+            schema = "./test-resources/test_schema.yaml",
+            rename(TestStruct = "crate::models::Test")
+        };
+        let input = quote! {
+            struct TestReferences;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestReferences {
+                pub test_struct: crate::models::Test,
+                pub test_enums: Option<Vec<TestEnum> >
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_missing_reference() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestMissingReference;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Err(Error::ModelNotFound("Missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_struct_external_reference() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestExternalReference;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestExternalReference {
+                pub external: ExternalStruct
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_parent_reference() {
+        let args = quote! { schema = "./test-resources/references/references_schema.yaml" };
+        let input = quote! {
+            struct TestParentReference;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/references/references_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestParentReference {
+                pub external: ExternalStruct
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_unfollowable_references() {
+        let args = quote! { schema = "./test-resources/references/references_schema.yaml" };
+        let args: AttributeArgs = parse2(args).unwrap();
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/test-resources");
+
+        let cases = vec![
+            (
+                quote! { struct TestRemoteReference; },
+                Error::UnsupportedReference(
+                    "https://example.com/schema.yaml#/components/schemas/Remote".to_string(),
+                ),
+            ),
+            (
+                quote! { struct TestWholeFileReference; },
+                Error::UnsupportedReference("../external_schema.yaml".to_string()),
+            ),
+        ];
+
+        for (input, err) in cases {
+            let input: DeriveInput = parse2(input).unwrap();
+            assert_eq!(from_openapi(&input, &args).map(|s| s.to_string()), Err(err));
+        }
+
+        let input: DeriveInput = parse2(quote! { struct TestMissingReference; }).unwrap();
+        assert!(matches!(
+            from_openapi(&input, &args),
+            Err(Error::FileOpenFailed(path, _))
+                if path == format!("{}/references/missing_schema.yaml", dir)
+        ));
+
+        let input: DeriveInput = parse2(quote! { struct TestInvalidReference; }).unwrap();
+        assert!(matches!(
+            from_openapi(&input, &args),
+            Err(Error::FileParseFailed(path, _)) if path == format!("{}/invalid_schema.json", dir)
+        ));
+    }
+
+    #[test]
+    fn test_struct_nullable() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestNullable;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestNullable {
+                pub name: Option<String>,
+                pub nickname: Option<String>,
+                pub age: Option<i32>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_alphabetical() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml", order = "alphabetical" };
+        let input = quote! {
+            struct TestNullable;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestNullable {
+                pub age: Option<i32>,
+                pub name: Option<String>,
+                pub nickname: Option<String>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_nullable_tri_state() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml", tri_state };
+        let input = quote! {
+            struct TestNullable;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestNullable {
+                pub name: Option<String>,
+                pub nickname: Option<Option<String> >,
+                pub age: Option<i32>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_union_tagged() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            #[derive(Debug)]
+            enum TestPet {
+                #[openapi(value = "cat")]
+                Kitty,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            #[derive(Debug)]
+            enum TestPet {
+                Kitty(TestCat),
+                TestDog(TestDog)
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_union_declared_tag() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            enum TestTaggedPet {}
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            enum TestTaggedPet {
+                Cat(TestTaggedCat),
+                Value1(TestTaggedDog),
+                TestDog(TestDog)
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_union_declared_tag_serde_only() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            enum TestTaggedPet {}
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let tokens = from_openapi(&input, &args).unwrap().to_string();
+        assert!(tokens.contains("impl < 'de > serde :: Deserialize < 'de > for TestTaggedPet"));
+        assert!(!tokens.contains("serde_json"));
+    }
+
+    #[test]
+    fn test_union_untagged() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            enum TestValue {}
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            enum TestValue {
+                TestStruct(TestStruct),
+                Integer(i32),
+                String(String)
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_all_of() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestDerived;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestDerived {
+                pub id: i64,
+                pub name: String,
+                pub owner: Option<TestStruct>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_all_of_flattened() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml", flatten_all_of };
+        let input = quote! {
+            struct TestExtended;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestExtended {
+                pub test_base: TestBase,
+                pub owner: Option<TestStruct>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_all_of_flattened_required() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml", flatten_all_of };
+        let input = quote! {
+            struct TestDerived;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        // The base's `name` is required by the other member, so it can't be flattened.
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestDerived {
+                pub id: i64,
+                pub name: String,
+                pub owner: Option<TestStruct>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_any() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let custom_args = quote! {
+            schema = "./test-resources/test_schema.yaml",
+            any_type = "crate::Json"
+        };
+        let input = quote! {
+            struct TestAny;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let custom_args: AttributeArgs = parse2(custom_args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestAny {
+                pub value: serde_json::Value,
+                pub metadata: Option<std::collections::HashMap<String, serde_json::Value>>
+            }
+            #dependencies
+        };
+        let expected_custom = quote! {
+            struct TestAny {
+                pub value: crate::Json,
+                pub metadata: Option<std::collections::HashMap<String, crate::Json>>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+        assert_eq!(
+            from_openapi(&input, &custom_args).map(without_serde),
+            Ok(without_serde(expected_custom))
+        );
+    }
+
+    #[test]
+    fn test_struct_all_of_conflict() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestConflict;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Err(Error::ConflictingFieldTypes("name".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_any_of_enum() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            enum TestScalar {}
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            enum TestScalar {
+                TestCat(TestCat),
+                Bool(bool),
+                Number(f32),
+                String(String)
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_any_of_struct_unmergeable() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestScalar;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Err(Error::UnsupportedStructType)
+        );
+    }
+
+    #[test]
+    fn test_struct_additional_properties() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml", btree_map };
+        let input = quote! {
+            struct TestExtra;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestExtra {
+                pub name: Option<String>,
+                pub extra: std::collections::BTreeMap<String, i32>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_additional_properties_clash() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestExtraClash;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestExtraClash {
+                pub extra: Option<String>,
+                pub additional_properties: std::collections::HashMap<String, i32>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_nested() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            pub struct TestNested;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let size_impls = test_nested_size_impls(quote!(pub));
+        let expected = quote! {
+            pub struct TestNested {
+                pub owner: Option<TestNestedOwner2>,
+                pub size: Option<TestNestedSize>,
+                pub tags: Option<Vec<TestNestedTags> >
+            }
+            pub struct TestNestedOwner2 {
+                pub name: String,
+                pub address: Option<TestNestedOwner2Address>
+            }
+            pub struct TestNestedOwner2Address {
+                pub city: Option<String>
+            }
+            pub enum TestNestedSize {
+                Small,
+                Large
+            }
+            #size_impls
+            pub struct TestNestedTags {
+                pub label: Option<String>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_struct_nested_type_name() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            #[derive(Debug)]
+            struct TestNested {
+                #[openapi(type_name = "Owner")]
+                owner: Option<Owner>,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let size_impls = test_nested_size_impls(quote!());
+        let expected = quote! {
+            #[derive(Debug)]
+            struct TestNested {
+                owner: Option<Owner>,
+                pub size: Option<TestNestedSize>,
+                pub tags: Option<Vec<TestNestedTags> >
+            }
+            #[derive(Debug)]
+            struct Owner {
+                pub name: String,
+                pub address: Option<OwnerAddress>
+            }
+            #[derive(Debug)]
+            struct OwnerAddress {
+                pub city: Option<String>
+            }
+            #[derive(Debug)]
+            enum TestNestedSize {
+                Small,
+                Large
+            }
+            #size_impls
+            #[derive(Debug)]
+            struct TestNestedTags {
+                pub label: Option<String>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_enum_integer() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            enum TestCode {
+                #[openapi(value = 5)]
+                NotFound,
+                Other = 2,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            #[repr(i64)]
+            enum TestCode {
+                Value1 = 1,
+                Other = 2,
+                NotFound = 5
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_enum_open() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml", open_enum };
+        let input = quote! {
+            enum TestEnum {
+                Other(String),
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            enum TestEnum {
+                Foo,
+                Bar,
+                Other(String)
+            }
+            impl TestEnum {
+                /// Every variant, in the order the schema lists them.
+                pub const ALL: &'static [Self] = &[Self::Foo, Self::Bar];
+
+                /// The value the variant represents in the schema.
+                pub fn as_str(&self) -> &str {
+                    match self {
+                        Self::Foo => "foo",
+                        Self::Bar => "bar",
+                        Self::Other(value) => value.as_str(),
+                    }
+                }
+            }
+            impl std::fmt::Display for TestEnum {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+            impl std::str::FromStr for TestEnum {
+                type Err = std::convert::Infallible;
+
+                fn from_str(value: &str) -> Result<Self, Self::Err> {
+                    Ok(match value {
+                        "foo" => Self::Foo,
+                        "bar" => Self::Bar,
+                        value => Self::Other(value.to_string()),
+                    })
+                }
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_enum_open_listed_unknown() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml", open_enum };
+        let input = quote! {
+            enum TestStatus {}
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        // The schema lists `unknown`, so the catch-all variant is called `Other` instead.
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            enum TestStatus {
+                Active,
+                Unknown,
+                Other(String)
+            }
+            impl TestStatus {
+                /// Every variant, in the order the schema lists them.
+                pub const ALL: &'static [Self] = &[Self::Active, Self::Unknown];
+
+                /// The value the variant represents in the schema.
+                pub fn as_str(&self) -> &str {
+                    match self {
+                        Self::Active => "active",
+                        Self::Unknown => "unknown",
+                        Self::Other(value) => value.as_str(),
+                    }
+                }
+            }
+            impl std::fmt::Display for TestStatus {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+            impl std::str::FromStr for TestStatus {
+                type Err = std::convert::Infallible;
+
+                fn from_str(value: &str) -> Result<Self, Self::Err> {
+                    Ok(match value {
+                        "active" => Self::Active,
+                        "unknown" => Self::Unknown,
+                        value => Self::Other(value.to_string()),
+                    })
+                }
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_enum_open_mismatched_catch_all() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml", open_enum };
+        let input = quote! {
+            enum TestEnum {
+                Other(Box<str>),
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Err(Error::MismatchedCatchAll(
+                "Other".to_string(),
+                "Other(String)".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_enum_closed_catch_all() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            enum TestEnum {
+                Unknown(String),
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Err(Error::InvalidEnumVariant("Unknown".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_struct_odd_names() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestOddNames;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            struct TestOddNames {
+                pub r#type: String,
+                pub r#ref: Option<String>,
+                pub value_1st: Option<i32>,
+                pub self_: Option<bool>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_enum_odd_values() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            enum TestOddValues {}
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let variants = quote! {
+            enum TestOddValues {
+                Value10,
+                Value,
+                ValueSelf,
+                InStock
+            }
+        };
+        assert!(without_serde(from_openapi(&input, &args).unwrap()).contains(&variants.to_string()));
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let args: AttributeArgs = parse2(args).unwrap();
+
+        let input: DeriveInput = parse2(quote! { enum TestClashingValues {} }).unwrap();
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Err(Error::DuplicateVariant("Red".to_string()))
+        );
+
+        let input: DeriveInput = parse2(quote! { struct TestClashingFields; }).unwrap();
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Err(Error::DuplicateField("foo_bar".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_duplicate_names_renamed() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            enum TestClashingValues {
+                #[openapi(value = "Red")]
+                Crimson,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        assert!(without_serde(from_openapi(&input, &args).unwrap()).contains("Red , Crimson"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_enum_serde() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            #[derive(serde::Serialize)]
+            enum TestEnum {}
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let impls = test_enum_impls();
+        let expected = quote! {
+            #[derive(serde::Serialize)]
+            #[derive(serde::Deserialize)]
+            enum TestEnum {
+                #[serde(rename = "foo")]
+                Foo,
+                #[serde(rename = "bar")]
+                Bar
+            }
+            #impls
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Ok(expected.to_string())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_struct_serde() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestSerde {
+                #[openapi(name = "lastName")]
+                surname: Option<String>,
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            #[derive(serde::Serialize, serde::Deserialize)]
+            struct TestSerde {
+                #[serde(rename = "firstName")]
+                pub first_name: String,
+                #[serde(rename = "lastName")]
+                #[serde(skip_serializing_if = "Option::is_none")]
+                surname: Option<String>,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                pub status: Option<TestEnum>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Ok(expected.to_string())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_struct_nullable_tri_state_serde() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml", tri_state };
+        let input = quote! {
+            struct TestNullable;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            #[derive(serde::Serialize, serde::Deserialize)]
+            struct TestNullable {
+                pub name: Option<String>,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                #[serde(default, deserialize_with = "TestNullable::__deserialize_nullable")]
+                pub nickname: Option<Option<String> >,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                pub age: Option<i32>
+            }
+            impl TestNullable {
+                #[doc(hidden)]
+                fn __deserialize_nullable<'de, D, T>(
+                    deserializer: D,
+                ) -> Result<Option<Option<T>>, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                    T: serde::Deserialize<'de>,
+                {
+                    <Option<T> as serde::Deserialize>::deserialize(deserializer).map(Some)
+                }
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Ok(expected.to_string())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_union_serde() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let tagged = quote! {
+            enum TestPet {}
+        };
+        let untagged = quote! {
+            enum TestValue {}
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let tagged: DeriveInput = parse2(tagged).unwrap();
+        let untagged: DeriveInput = parse2(untagged).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected_tagged = quote! {
+            #[derive(serde::Serialize, serde::Deserialize)]
+            #[serde(tag = "petType")]
+            enum TestPet {
+                #[serde(rename = "cat")]
+                Cat(TestCat),
+                TestDog(TestDog)
+            }
+            #dependencies
+        };
+        let expected_untagged = quote! {
+            #[derive(serde::Serialize, serde::Deserialize)]
+            #[serde(untagged)]
+            enum TestValue {
+                TestStruct(TestStruct),
+                Integer(i32),
+                String(String)
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&tagged, &args).map(|s| s.to_string()),
+            Ok(expected_tagged.to_string())
+        );
+        assert_eq!(
+            from_openapi(&untagged, &args).map(|s| s.to_string()),
+            Ok(expected_untagged.to_string())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_struct_all_of_flattened_serde() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml", flatten_all_of };
+        let input = quote! {
+            struct TestExtended;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            #[derive(serde::Serialize, serde::Deserialize)]
+            struct TestExtended {
+                #[serde(flatten)]
+                pub test_base: TestBase,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                pub owner: Option<TestStruct>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Ok(expected.to_string())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_struct_additional_properties_serde() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestExtra;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            #[derive(serde::Serialize, serde::Deserialize)]
+            struct TestExtra {
+                #[serde(skip_serializing_if = "Option::is_none")]
+                pub name: Option<String>,
+                #[serde(flatten)]
+                pub extra: std::collections::HashMap<String, i32>
+            }
+            #dependencies
+        };
+
+        assert_eq!(
+            from_openapi(&input, &args).map(|s| s.to_string()),
+            Ok(expected.to_string())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_struct_deny_unknown_fields_serde() {
+        let args = quote! { schema = "./test-resources/test_schema.yaml" };
+        let input = quote! {
+            struct TestStrict;
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: DeriveInput = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/test_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let expected = quote! {
+            #[derive(serde::Serialize, serde::Deserialize)]
+            #[serde(deny_unknown_fields)]
+            struct TestStrict {
+                #[serde(skip_serializing_if = "Option::is_none")]
+                pub name: Option<String>
             }
+            #dependencies
         };
 
         assert_eq!(
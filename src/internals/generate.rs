@@ -0,0 +1,592 @@
+use crate::internals::ast::{Container, EnumContainer, StructContainer};
+use crate::internals::attrs::OpenApiArgs;
+use crate::internals::codegen::{Alias, Enum, Formats, Struct, Union};
+use crate::internals::error::Error;
+use crate::internals::resolver::{type_name, Resolver};
+use crate::internals::schema::{ComponentSchema, TryFromSchema};
+use crate::internals::spec::Spec;
+use darling::{FromDeriveInput, FromMeta};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use std::collections::HashMap;
+use syn::{AttributeArgs, Ident, Item, ItemMod, VisPublic, Visibility};
+
+/// Generates a type for every component schema in the document.
+pub fn generate(attrs: &AttributeArgs) -> Result<TokenStream, Error> {
+    let args: OpenApiArgs = OpenApiArgs::from_list(attrs)?;
+
+    generate_components(&args, vec![])
+}
+
+/// Generates a type for every component schema in the document inside `module`. Structs and enums
+/// already declared in the module are treated as stubs for the component with the same name, in
+/// the same way as types annotated with `#[from_openapi]`.
+pub fn from_openapi_module(module: &ItemMod, attrs: &AttributeArgs) -> Result<TokenStream, Error> {
+    let args: OpenApiArgs = OpenApiArgs::from_list(attrs)?;
+
+    let items = match &module.content {
+        Some((_, items)) => items.clone(),
+        None => return Err(Error::ModuleNotInline),
+    };
+
+    let attrs = &module.attrs;
+    let vis = &module.vis;
+    let ident = &module.ident;
+    let content = generate_components(&args, items)?;
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis mod #ident {
+            #content
+        }
+    })
+}
+
+fn generate_components(args: &OpenApiArgs, mut items: Vec<Item>) -> Result<TokenStream, Error> {
+    // Every component is generated, so there's no single one for `ref` to pick out.
+    if args.reference.is_some() {
+        return Err(Error::UnsupportedModuleArgument("ref".to_string()));
+    }
+
+    let spec = Spec::load(&args.schema)?;
+    let resolver = Resolver::new(&spec, args);
+
+    // References to components generated as type aliases are serialized as the types the aliases
+    // stand for. Stubs are left out, as they may be newtypes serialized in their own way.
+    let mut aliases = HashMap::new();
+    for (name, component) in resolver.components_schemas()? {
+        let ident = type_name(name)?;
+        if resolver.is_renamed(name) || stub_index(&items, &ident).is_some() {
+            continue;
+        }
+
+        let scope = ident.to_string();
+        if let ComponentSchema::Alias(ty, _) =
+            ComponentSchema::try_from_schema(component, &resolver.scoped(&scope).declared(name))?
+        {
+            aliases.insert(name.clone(), ty);
+        }
+    }
+    let resolver = resolver.with_aliases(&aliases);
+
+    let mut tokens = TokenStream::new();
+    let mut formats = Formats::shared();
+
+    for (name, component) in resolver.components_schemas()? {
+        if resolver.is_renamed(name) {
+            continue;
+        }
+
+        let ident = type_name(name)?;
+        let stub = take_stub(&mut items, &ident);
+
+        let scope = ident.to_string();
+
+        match ComponentSchema::try_from_schema(component, &resolver.scoped(&scope).declared(name))?
+        {
+            ComponentSchema::Struct(schema) => {
+                let container = match stub {
+                    Some(Item::Struct(stub)) => Container::from_derive_input(&stub.into())?.into(),
+                    Some(_) => return Err(Error::MismatchedStubKind(scope)),
+                    None => StructContainer::new(ident, public()),
+                };
+                Struct::combined(container, schema, &mut formats)?.to_tokens(&mut tokens);
+            }
+            ComponentSchema::Enum(schema) => {
+                let container = match stub {
+                    Some(Item::Enum(stub)) => Container::from_derive_input(&stub.into())?.into(),
+                    Some(_) => return Err(Error::MismatchedStubKind(scope)),
+                    None => EnumContainer::new(ident, public()),
+                };
+                Enum::combined(container, schema)?.to_tokens(&mut tokens);
+            }
+            ComponentSchema::Union(schema) => {
+                let container = match stub {
+                    Some(Item::Enum(stub)) => Container::from_derive_input(&stub.into())?.into(),
+                    Some(_) => return Err(Error::MismatchedStubKind(scope)),
+                    None => EnumContainer::new(ident, public()),
+                };
+                Union::combined(container, schema, &mut formats)?.to_tokens(&mut tokens);
+            }
+            // Other types are taken verbatim from the stub, which may be a type alias or a
+            // newtype wrapping the schema's type.
+            ComponentSchema::Alias(schema, docs) => match stub {
+                Some(stub) => stub.to_tokens(&mut tokens),
+                None => Alias::new(ident, public(), &schema, &docs).to_tokens(&mut tokens),
+            },
+        }
+    }
+
+    // Anything which doesn't correspond to a component is left as it is.
+    for item in items {
+        item.to_tokens(&mut tokens);
+    }
+
+    formats.to_tokens(&mut tokens);
+
+    tokens.extend(spec.dependency_tokens());
+
+    Ok(tokens)
+}
+
+fn public() -> Visibility {
+    Visibility::Public(VisPublic {
+        pub_token: Default::default(),
+    })
+}
+
+/// Removes the struct, enum or type alias called `ident` from `items`.
+fn take_stub(items: &mut Vec<Item>, ident: &Ident) -> Option<Item> {
+    let index = stub_index(items, ident)?;
+
+    Some(items.remove(index))
+}
+
+/// The position of the struct, enum or type alias called `ident` in `items`.
+fn stub_index(items: &[Item], ident: &Ident) -> Option<usize> {
+    items.iter().position(|item| match item {
+        Item::Struct(item) => item.ident == *ident,
+        Item::Enum(item) => item.ident == *ident,
+        Item::Type(item) => item.ident == *ident,
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internals::generate::{from_openapi_module, generate};
+    use crate::internals::spec::Spec;
+    use crate::internals::utils::{parse2, without_serde};
+    use crate::internals::Error;
+    use proc_macro2::TokenStream;
+    use quote::quote;
+    use syn::{AttributeArgs, ItemMod};
+
+    /// The impls generated alongside `PetStatus`, which is declared with `vis`.
+    fn pet_status_impls(vis: TokenStream) -> TokenStream {
+        quote! {
+            impl PetStatus {
+                /// Every variant, in the order the schema lists them.
+                pub const ALL: &'static [Self] = &[Self::Available, Self::Sold];
+
+                /// The value the variant represents in the schema.
+                pub fn as_str(&self) -> &'static str {
+                    match self {
+                        Self::Available => "available",
+                        Self::Sold => "sold",
+                    }
+                }
+            }
+
+            impl std::fmt::Display for PetStatus {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+
+            impl std::str::FromStr for PetStatus {
+                type Err = ParsePetStatusError;
+
+                fn from_str(value: &str) -> Result<Self, Self::Err> {
+                    match value {
+                        "available" => Ok(Self::Available),
+                        "sold" => Ok(Self::Sold),
+                        value => Err(ParsePetStatusError {
+                            value: value.to_string(),
+                        }),
+                    }
+                }
+            }
+
+            #[doc = "The error returned when parsing a string which isn't one of the values of [PetStatus]."]
+            #[derive(Clone, Debug, PartialEq, Eq)]
+            #vis struct ParsePetStatusError {
+                value: String,
+            }
+
+            impl std::fmt::Display for ParsePetStatusError {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "unknown PetStatus value `{}`", self.value)
+                }
+            }
+
+            impl std::error::Error for ParsePetStatusError {}
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        let args = quote! { schema = "./test-resources/generate_schema.yaml" };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+
+        let dependencies = Spec::load("./test-resources/generate_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let status_impls = pet_status_impls(quote!(pub));
+        let expected = quote! {
+            pub struct Pet {
+                pub id: PetId,
+                pub name: String,
+                pub status: Option<PetStatus>,
+                pub tags: Option<Vec<Tag> >
+            }
+            #[doc = " Pet ID"]
+            #[doc = ""]
+            #[doc = " Identifies a pet."]
+            #[doc = ""]
+            #[doc = " # Example"]
+            #[doc = ""]
+            #[doc = " ```json"]
+            #[doc = " 42"]
+            #[doc = " ```"]
+            pub type PetId = i64;
+            pub enum PetStatus {
+                Available,
+                Sold
+            }
+            #status_impls
+            pub struct Tag {
+                pub label: Option<String>
+            }
+            pub type Label = Tag;
+            pub enum PetOrTag {
+                Pet(Pet),
+                Tag(Tag)
+            }
+            pub type Labels = std::collections::HashMap<String, Tag>;
+            #dependencies
+        };
+
+        assert_eq!(
+            generate(&args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_generate_type_map() {
+        let args = quote! {
+            schema = "./test-resources/generate_schema.yaml",
+            type_map(PetId = "crate::Id", Tag = "crate::Tag")
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+
+        let dependencies = Spec::load("./test-resources/generate_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let status_impls = pet_status_impls(quote!(pub));
+        let expected = quote! {
+            pub struct Pet {
+                pub id: crate::Id,
+                pub name: String,
+                pub status: Option<PetStatus>,
+                pub tags: Option<Vec<crate::Tag> >
+            }
+            pub enum PetStatus {
+                Available,
+                Sold
+            }
+            #status_impls
+            pub type Label = crate::Tag;
+            pub enum PetOrTag {
+                Pet(Pet),
+                Tag(crate::Tag)
+            }
+            pub type Labels = std::collections::HashMap<String, crate::Tag>;
+            #dependencies
+        };
+
+        assert_eq!(
+            generate(&args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_generate_serde() {
+        let args = quote! { schema = "./test-resources/generate_schema.yaml" };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+
+        let dependencies = Spec::load("./test-resources/generate_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let status_impls = pet_status_impls(quote!(pub));
+        let expected = quote! {
+            #[derive(serde::Serialize, serde::Deserialize)]
+            pub struct Pet {
+                pub id: PetId,
+                pub name: String,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                pub status: Option<PetStatus>,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                pub tags: Option<Vec<Tag> >
+            }
+            #[doc = " Pet ID"]
+            #[doc = ""]
+            #[doc = " Identifies a pet."]
+            #[doc = ""]
+            #[doc = " # Example"]
+            #[doc = ""]
+            #[doc = " ```json"]
+            #[doc = " 42"]
+            #[doc = " ```"]
+            pub type PetId = i64;
+            #[derive(serde::Serialize, serde::Deserialize)]
+            pub enum PetStatus {
+                #[serde(rename = "available")]
+                Available,
+                #[serde(rename = "sold")]
+                Sold
+            }
+            #status_impls
+            #[derive(serde::Serialize, serde::Deserialize)]
+            pub struct Tag {
+                #[serde(skip_serializing_if = "Option::is_none")]
+                pub label: Option<String>
+            }
+            pub type Label = Tag;
+            #[derive(serde::Serialize, serde::Deserialize)]
+            #[serde(untagged)]
+            pub enum PetOrTag {
+                Pet(Pet),
+                Tag(Tag)
+            }
+            pub type Labels = std::collections::HashMap<String, Tag>;
+            #dependencies
+        };
+
+        assert_eq!(
+            generate(&args).map(|s| s.to_string()),
+            Ok(expected.to_string())
+        );
+    }
+
+    #[test]
+    fn test_module_with_stubs() {
+        let args = quote! {
+            schema = "./test-resources/generate_schema.yaml",
+            rename(Label = "super::Label")
+        };
+        let input = quote! {
+            pub mod models {
+                use std::fmt;
+
+                #[derive(Debug)]
+                pub enum PetStatus {}
+
+                #[derive(Debug)]
+                pub(crate) struct Tag {
+                    #[openapi(name = "label")]
+                    pub text: String,
+                }
+
+                pub type PetId = u64;
+
+                pub struct Other;
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: ItemMod = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/generate_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let status_impls = pet_status_impls(quote!(pub));
+        let expected = quote! {
+            pub mod models {
+                pub struct Pet {
+                    pub id: PetId,
+                    pub name: String,
+                    pub status: Option<PetStatus>,
+                    pub tags: Option<Vec<Tag> >
+                }
+                pub type PetId = u64;
+                #[derive(Debug)]
+                pub enum PetStatus {
+                    Available,
+                    Sold
+                }
+                #status_impls
+                #[derive(Debug)]
+                pub(crate) struct Tag {
+                    pub text: String
+                }
+                pub enum PetOrTag {
+                    Pet(Pet),
+                    Tag(Tag)
+                }
+                pub type Labels = std::collections::HashMap<String, Tag>;
+                use std::fmt;
+                pub struct Other;
+                #dependencies
+            }
+        };
+
+        assert_eq!(
+            from_openapi_module(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[test]
+    fn test_generate_any_of_struct() {
+        let args = quote! {
+            schema = "./test-resources/generate_schema.yaml",
+            any_of = "struct"
+        };
+        let input = quote! {
+            mod models {
+                struct Pet;
+                struct Tag;
+                type PetId = i64;
+                enum PetStatus {}
+                type Label = Tag;
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: ItemMod = parse2(input).unwrap();
+
+        let dependencies = Spec::load("./test-resources/generate_schema.yaml")
+            .unwrap()
+            .dependency_tokens();
+        let status_impls = pet_status_impls(quote!());
+        let expected = quote! {
+            mod models {
+                struct Pet {
+                    pub id: PetId,
+                    pub name: String,
+                    pub status: Option<PetStatus>,
+                    pub tags: Option<Vec<Tag> >
+                }
+                type PetId = i64;
+                enum PetStatus {
+                    Available,
+                    Sold
+                }
+                #status_impls
+                struct Tag {
+                    pub label: Option<String>
+                }
+                type Label = Tag;
+                pub struct PetOrTag {
+                    pub label: Option<String>,
+                    pub id: Option<PetId>,
+                    pub name: Option<String>,
+                    pub status: Option<PetStatus>,
+                    pub tags: Option<Vec<Tag> >
+                }
+                pub type Labels = std::collections::HashMap<String, Tag>;
+                #dependencies
+            }
+        };
+
+        assert_eq!(
+            from_openapi_module(&input, &args).map(without_serde),
+            Ok(without_serde(expected))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_generate_shared_formats() {
+        let args = quote! { schema = "./test-resources/bytes_schema.yaml" };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+
+        let tokens = generate(&args).unwrap().to_string();
+        assert_eq!(tokens.matches("mod __formats").count(), 1);
+        assert_eq!(tokens.matches("pub struct Base64").count(), 1);
+        assert!(tokens.contains(r#"with = "__formats::As::<__formats::Base64>""#));
+        assert!(tokens.contains(
+            r#"with = "__formats::As::<__formats::Nullable<__formats::Array<__formats::Base64>>>""#
+        ));
+    }
+
+    #[test]
+    fn test_generate_nested_names() {
+        let args = quote! { schema = "./test-resources/nested_schema.yaml" };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+
+        let tokens = generate(&args).unwrap().to_string();
+        assert!(tokens.contains("pub struct ABC {"));
+        assert!(tokens.contains("pub struct ABC2 {"));
+        assert!(tokens.contains("pub b_c : Option < ABC >"));
+        assert!(tokens.contains("pub c : Option < ABC2 >"));
+        assert!(tokens.contains("pub struct ParsePetError {"));
+        assert!(tokens.contains("pub enum ParsePetError2 {"));
+        assert!(tokens.contains("pub struct ShapeVariant1 {"));
+        assert!(tokens.contains("pub struct ShapeVariant12 {"));
+    }
+
+    #[test]
+    fn test_module_mismatched_stub() {
+        let args = quote! { schema = "./test-resources/generate_schema.yaml" };
+        let input = quote! {
+            mod models {
+                struct PetStatus;
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: ItemMod = parse2(input).unwrap();
+
+        assert_eq!(
+            from_openapi_module(&input, &args).map(|s| s.to_string()),
+            Err(Error::MismatchedStubKind("PetStatus".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_module_mismatched_struct_stub() {
+        let args = quote! { schema = "./test-resources/generate_schema.yaml" };
+        let input = quote! {
+            mod models {
+                enum Pet {}
+            }
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: ItemMod = parse2(input).unwrap();
+
+        assert_eq!(
+            from_openapi_module(&input, &args).map(|s| s.to_string()),
+            Err(Error::MismatchedStubKind("Pet".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_generate_reference() {
+        let args = quote! {
+            schema = "./test-resources/generate_schema.yaml",
+            ref = "#/components/schemas/Pet"
+        };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+
+        assert_eq!(
+            generate(&args).map(|s| s.to_string()),
+            Err(Error::UnsupportedModuleArgument("ref".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_module_not_inline() {
+        let args = quote! { schema = "./test-resources/generate_schema.yaml" };
+        let input = quote! { mod models; };
+
+        let args: AttributeArgs = parse2(args).unwrap();
+        let input: ItemMod = parse2(input).unwrap();
+
+        assert_eq!(
+            from_openapi_module(&input, &args).map(|s| s.to_string()),
+            Err(Error::ModuleNotInline)
+        );
+    }
+}
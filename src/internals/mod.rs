@@ -5,9 +5,13 @@ mod check_openapi;
 mod codegen;
 mod error;
 mod from_openapi;
+mod generate;
+mod resolver;
 mod schema;
+mod spec;
 mod utils;
 
 pub use check_openapi::check_openapi;
 pub use error::Error;
 pub use from_openapi::from_openapi;
+pub use generate::{from_openapi_module, generate};
@@ -0,0 +1,549 @@
+use crate::internals::attrs::{AnyOf, OpenApiArgs, Order};
+use crate::internals::error::Error;
+use crate::internals::schema::TypeSchema;
+use crate::internals::spec::{external_path, Spec};
+use heck::CamelCase;
+use openapiv3::{Components, ReferenceOr, Schema};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::Path as FsPath;
+use std::rc::Rc;
+use syn::{Ident, Path};
+
+/// Resolves `$ref`s against the components of the documents in a [Spec].
+#[derive(Clone)]
+pub struct Resolver<'a> {
+    spec: &'a Spec,
+    document: &'a FsPath,
+    /// The JSON pointer to the schema being converted within `document`, by which its
+    /// `x-rust-type` extension is found.
+    pointer: Rc<str>,
+    renames: &'a HashMap<String, Path>,
+    tri_state: bool,
+    flatten_all_of: bool,
+    any_of: AnyOf,
+    btree_map: bool,
+    open_enum: bool,
+    infer_unsigned: bool,
+    order: Order,
+    formats: &'a HashMap<String, Path>,
+    type_map: &'a HashMap<String, Path>,
+    any_type: Option<&'a Path>,
+    /// The components generated as type aliases, by name, when every component is generated.
+    aliases: Option<&'a HashMap<String, TypeSchema>>,
+    scope: &'a str,
+    /// The member of `scope`, such as a property, that a schema declared inline is named after.
+    member: Option<&'a str>,
+    /// The names of the types generated by the macro, shared by every resolver derived from the
+    /// same one.
+    names: Rc<RefCell<Names>>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(spec: &'a Spec, args: &'a OpenApiArgs) -> Self {
+        let mut names = Names::default();
+        if let Some(components) = spec
+            .document(spec.root())
+            .and_then(|(_, openapi)| openapi.components.as_ref())
+        {
+            for name in components.schemas.keys() {
+                if let Ok(ident) = type_name(name) {
+                    names.reserve(ident.to_string());
+                }
+            }
+        }
+
+        Self {
+            spec,
+            document: spec.root(),
+            pointer: Rc::from(""),
+            renames: &args.rename,
+            tri_state: args.tri_state,
+            flatten_all_of: args.flatten_all_of,
+            any_of: args.any_of,
+            btree_map: args.btree_map,
+            open_enum: args.open_enum,
+            infer_unsigned: args.infer_unsigned,
+            order: args.order,
+            formats: &args.formats,
+            type_map: &args.type_map,
+            any_type: args.any_type.as_ref(),
+            aliases: None,
+            scope: "",
+            member: None,
+            names: Rc::new(RefCell::new(names)),
+        }
+    }
+
+    /// Looks up the component schema called `name`, following any components which are
+    /// themselves references to other components. The schema is returned along with a resolver
+    /// for the component it was found in.
+    pub fn component(&self, name: &str) -> Result<(Self, &'a Schema), Error> {
+        self.lookup(self.document, name.to_string())
+    }
+
+    /// The component schemas of the root document, in the order they are declared.
+    pub fn components_schemas(
+        &self,
+    ) -> Result<impl Iterator<Item = (&'a String, &'a ReferenceOr<Schema>)>, Error> {
+        Ok(self.components(self.document)?.schemas.iter())
+    }
+
+    /// Whether the component called `name` has been mapped to a Rust type defined elsewhere,
+    /// either by the macro's arguments or by its `x-rust-type` extension.
+    pub fn is_renamed(&self, name: &str) -> bool {
+        self.renamed(name).is_some()
+            || self
+                .component(name)
+                .is_ok_and(|(resolver, _)| resolver.extension().is_some())
+    }
+
+    /// The Rust type the macro's arguments map the component called `name` to, if any.
+    fn renamed(&self, name: &str) -> Option<&'a Path> {
+        self.renames.get(name).or_else(|| self.type_map.get(name))
+    }
+
+    /// The Rust type given by the `x-rust-type` extension of the schema being converted, if it has
+    /// one.
+    fn rust_type(&self) -> Result<Option<Path>, Error> {
+        match self.extension() {
+            Some(rust_type) => syn::parse_str(rust_type)
+                .map(Some)
+                .map_err(|_| Error::InvalidRustType(rust_type.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    fn extension(&self) -> Option<&'a str> {
+        self.spec.rust_type(self.document, &self.pointer)
+    }
+
+    /// Returns a resolver for the component called `name`, declared in the current document.
+    pub fn declared(&self, name: &str) -> Self {
+        Self {
+            pointer: Rc::from(component_pointer(name)),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a resolver for the schema declared inline at `tokens` within the current one, such
+    /// as `["properties", "name"]`.
+    pub fn child(&self, tokens: &[&str]) -> Self {
+        Self {
+            pointer: Rc::from(child_pointer(&self.pointer, tokens)),
+            ..self.clone()
+        }
+    }
+
+    /// Whether optional nullable fields should distinguish being absent from being `null`.
+    pub fn tri_state(&self) -> bool {
+        self.tri_state
+    }
+
+    /// Whether referenced members of an `allOf` should be kept as flattened fields.
+    pub fn flatten_all_of(&self) -> bool {
+        self.flatten_all_of
+    }
+
+    /// How `anyOf` schemas should be generated.
+    pub fn any_of(&self) -> AnyOf {
+        self.any_of
+    }
+
+    /// Whether maps should be generated as `BTreeMap`s.
+    pub fn btree_map(&self) -> bool {
+        self.btree_map
+    }
+
+    /// Whether enums should have a variant for values the schema doesn't list.
+    pub fn open_enum(&self) -> bool {
+        self.open_enum
+    }
+
+    /// Whether integers which can't be negative should be generated as unsigned.
+    pub fn infer_unsigned(&self) -> bool {
+        self.infer_unsigned
+    }
+
+    /// The order generated fields and variants should be declared in.
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    /// The Rust type the user has given for values the schema places no restrictions on, if any.
+    pub fn any_type(&self) -> Option<&'a Path> {
+        self.any_type
+    }
+
+    /// The Rust type the user has chosen for the schema being converted, whose format is `format`,
+    /// if any. In order of precedence, that is the type given by the schema's `x-rust-type`
+    /// extension, then the one the `type_map` argument maps the format to, and then the one the
+    /// `formats` argument does. Otherwise, the type is generated from the schema, using the types
+    /// built in for the formats known here.
+    pub fn custom_type(&self, format: Option<&str>) -> Result<Option<Path>, Error> {
+        if let Some(path) = self.rust_type()? {
+            return Ok(Some(path));
+        }
+
+        let format = match format {
+            Some(format) => format.replace('-', "_"),
+            None => return Ok(None),
+        };
+
+        Ok(self
+            .type_map
+            .get(&format)
+            .or_else(|| self.formats.get(&format))
+            .cloned())
+    }
+
+    /// Returns a resolver which knows which of the root document's components are generated as
+    /// the type aliases `aliases`.
+    pub fn with_aliases<'b>(&self, aliases: &'b HashMap<String, TypeSchema>) -> Resolver<'b>
+    where
+        'a: 'b,
+    {
+        let resolver: Resolver<'b> = self.clone();
+        Resolver {
+            aliases: Some(aliases),
+            ..resolver
+        }
+    }
+
+    /// The type the component `reference` points at stands for, if it is generated as a type
+    /// alias.
+    pub fn alias(&self, reference: &str) -> Result<Option<&'a TypeSchema>, Error> {
+        let (document, name) = self.target(self.document, reference)?;
+
+        match self.aliases {
+            Some(aliases) if document == self.spec.root() => Ok(aliases.get(&name)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns a resolver which gives schemas declared inline the name `scope`.
+    pub fn scoped<'b>(&self, scope: &'b str) -> Resolver<'b>
+    where
+        'a: 'b,
+    {
+        let resolver: Resolver<'b> = self.clone();
+        Resolver {
+            scope,
+            member: None,
+            ..resolver
+        }
+    }
+
+    /// Returns a resolver which names schemas declared inline after `member` of the current scope,
+    /// such as one of its properties.
+    pub fn member<'b>(&self, member: &'b str) -> Resolver<'b>
+    where
+        'a: 'b,
+    {
+        let resolver: Resolver<'b> = self.clone();
+        Resolver {
+            member: Some(member),
+            ..resolver
+        }
+    }
+
+    /// Names the type generated for a schema declared inline as the current member of the scope.
+    /// The name is the scope's followed by the member's, with a number appended if another type
+    /// generated by the macro, or the error type parsing it, already has that name. The same
+    /// member is always given the same name.
+    pub fn nested_name(&self) -> String {
+        let member = match self.member {
+            Some(member) => member,
+            None => return self.scope.to_string(),
+        };
+        let key = (self.scope.to_string(), member.to_string());
+        let mut names = self.names.borrow_mut();
+
+        if let Some(name) = names.given.get(&key) {
+            return name.clone();
+        }
+
+        let base = format!("{}{}", self.scope, member.to_camel_case());
+        let mut name = base.clone();
+        let mut suffix = 2;
+
+        while names.is_taken(&name) {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+
+        names.reserve(name.clone());
+        names.given.insert(key, name.clone());
+        name
+    }
+
+    /// Looks up the component schema `reference` points at. The schema is returned along with a
+    /// resolver for the document it was found in, which any references it contains are relative
+    /// to.
+    pub fn resolve(&self, reference: &str) -> Result<(Self, &'a Schema), Error> {
+        let (document, name) = self.target(self.document, reference)?;
+        self.lookup(document, name)
+    }
+
+    /// Returns the name of the component `reference` points at.
+    pub fn reference_name(&self, reference: &str) -> Result<String, Error> {
+        self.target(self.document, reference).map(|(_, name)| name)
+    }
+
+    /// Resolves `reference` to the path of the Rust type generated for the component it points at.
+    pub fn type_path(&self, reference: &str) -> Result<Path, Error> {
+        let (document, name) = self.target(self.document, reference)?;
+        let (resolver, _) = self.lookup(document, name.clone())?;
+
+        match self.renamed(&name) {
+            Some(path) => Ok(path.clone()),
+            None => match resolver.rust_type()? {
+                Some(path) => Ok(path),
+                None => type_name(&name).map(Path::from),
+            },
+        }
+    }
+
+    fn components(&self, document: &FsPath) -> Result<&'a Components, Error> {
+        self.spec
+            .document(document)
+            .and_then(|(_, openapi)| openapi.components.as_ref())
+            .ok_or(Error::MissingComponents)
+    }
+
+    /// Looks up the component called `name` in `document`, returning it along with a resolver for
+    /// the component.
+    fn lookup(
+        &self,
+        mut document: &'a FsPath,
+        mut name: String,
+    ) -> Result<(Self, &'a Schema), Error> {
+        let mut visited = HashSet::new();
+
+        loop {
+            if !visited.insert((document, name.clone())) {
+                return Err(Error::CircularReference(name));
+            }
+
+            match self.components(document)?.schemas.get(&name) {
+                Some(ReferenceOr::Item(schema)) => {
+                    let resolver = Self {
+                        document,
+                        pointer: Rc::from(component_pointer(&name)),
+                        ..self.clone()
+                    };
+                    return Ok((resolver, schema));
+                }
+                Some(ReferenceOr::Reference { reference }) => {
+                    let (doc, n) = self.target(document, reference)?;
+                    document = doc;
+                    name = n;
+                }
+                None => return Err(Error::ModelNotFound(name)),
+            }
+        }
+    }
+
+    /// Returns the document and name of the component `reference`, found in `document`, points at.
+    fn target(&self, document: &'a FsPath, reference: &str) -> Result<(&'a FsPath, String), Error> {
+        let (file, pointer) = split_reference(reference)?;
+
+        let document = match file {
+            Some(file) => {
+                let path = external_path(document, file);
+                match self.spec.document(&path) {
+                    Some((document, _)) => document,
+                    None => {
+                        return Err(self
+                            .spec
+                            .load_error(&path)
+                            .unwrap_or_else(|| Error::UnsupportedReference(reference.to_string())))
+                    }
+                }
+            }
+            None => document,
+        };
+
+        Ok((document, component_name(reference, pointer)?))
+    }
+}
+
+/// The names of the types generated by the macro, along with the error types generated for
+/// parsing enums, which nested types must not clash with.
+#[derive(Default)]
+struct Names {
+    taken: HashSet<String>,
+    /// The names given to nested types, by the scope and member they are declared as.
+    given: HashMap<(String, String), String>,
+}
+
+impl Names {
+    fn reserve(&mut self, name: String) {
+        self.taken.insert(format!("Parse{}Error", name));
+        self.taken.insert(name);
+    }
+
+    fn is_taken(&self, name: &str) -> bool {
+        self.taken.contains(name) || self.taken.contains(&format!("Parse{}Error", name))
+    }
+}
+
+/// Returns the Rust identifier used for the component called `name`. Names which are already
+/// valid identifiers are kept as they are, anything else is converted to camel case.
+pub fn type_name(name: &str) -> Result<Ident, Error> {
+    syn::parse_str(name)
+        .or_else(|_| syn::parse_str(&name.to_camel_case()))
+        .map_err(|_| Error::InvalidTypeName(name.to_string()))
+}
+
+/// Splits `reference` into the file it refers to, if it is not in the current document, and the
+/// JSON pointer into that file.
+pub fn split_reference(reference: &str) -> Result<(Option<&str>, &str), Error> {
+    match reference.find('#') {
+        Some(_) if reference.contains("://") => {
+            Err(Error::UnsupportedReference(reference.to_string()))
+        }
+        Some(0) => Ok((None, &reference[1..])),
+        Some(i) => Ok((Some(&reference[..i]), &reference[i + 1..])),
+        None => Err(Error::UnsupportedReference(reference.to_string())),
+    }
+}
+
+/// Extracts the component name from the JSON pointer of a reference, which must be of the form
+/// `/components/schemas/{name}`.
+fn component_name(reference: &str, pointer: &str) -> Result<String, Error> {
+    let tokens =
+        parse_pointer(pointer).ok_or_else(|| Error::InvalidReference(reference.to_string()))?;
+
+    match tokens.as_slice() {
+        [components, schemas, name] if components == "components" && schemas == "schemas" => {
+            Ok(name.to_owned())
+        }
+        _ => Err(Error::UnsupportedReference(reference.to_string())),
+    }
+}
+
+/// Splits a JSON pointer (RFC 6901) held in a URI fragment into its unescaped reference tokens.
+fn parse_pointer(pointer: &str) -> Option<Vec<String>> {
+    let pointer = percent_decode(pointer)?;
+
+    if pointer.is_empty() {
+        return Some(vec![]);
+    }
+
+    pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(|token| {
+            let mut unescaped = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '~' => match chars.next() {
+                        Some('0') => unescaped.push('~'),
+                        Some('1') => unescaped.push('/'),
+                        _ => return None,
+                    },
+                    _ => unescaped.push(c),
+                }
+            }
+            Some(unescaped)
+        })
+        .collect()
+}
+
+/// The JSON pointer to the component called `name`.
+pub fn component_pointer(name: &str) -> String {
+    child_pointer("", &["components", "schemas", name])
+}
+
+/// Appends the reference tokens `tokens` to `pointer`, escaping them as RFC 6901 requires.
+pub fn child_pointer(pointer: &str, tokens: &[&str]) -> String {
+    tokens.iter().fold(pointer.to_string(), |pointer, token| {
+        format!(
+            "{}/{}",
+            pointer,
+            token.replace('~', "~0").replace('/', "~1")
+        )
+    })
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internals::resolver::{component_name, split_reference, type_name};
+    use crate::internals::Error;
+
+    #[test]
+    fn test_split_reference() {
+        assert_eq!(
+            split_reference("#/components/schemas/Foo"),
+            Ok((None, "/components/schemas/Foo"))
+        );
+        assert_eq!(
+            split_reference("../models.yaml#/components/schemas/Foo"),
+            Ok((Some("../models.yaml"), "/components/schemas/Foo"))
+        );
+        assert_eq!(
+            split_reference("https://example.com/models.yaml#/components/schemas/Foo"),
+            Err(Error::UnsupportedReference(
+                "https://example.com/models.yaml#/components/schemas/Foo".to_string()
+            ))
+        );
+        assert_eq!(
+            split_reference("models.yaml"),
+            Err(Error::UnsupportedReference("models.yaml".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_component_name() {
+        let component_name =
+            |reference| split_reference(reference).and_then(|(_, p)| component_name(reference, p));
+
+        assert_eq!(
+            component_name("#/components/schemas/Foo"),
+            Ok("Foo".to_string())
+        );
+        assert_eq!(
+            component_name("#/components/schemas/Foo~1Bar~0%20Baz"),
+            Ok("Foo/Bar~ Baz".to_string())
+        );
+        assert_eq!(
+            component_name("#/components/responses/Foo"),
+            Err(Error::UnsupportedReference(
+                "#/components/responses/Foo".to_string()
+            ))
+        );
+        assert_eq!(
+            component_name("#/components/schemas/Foo~2"),
+            Err(Error::InvalidReference(
+                "#/components/schemas/Foo~2".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(type_name("FooBar").unwrap(), "FooBar");
+        assert_eq!(type_name("HTTPStatus").unwrap(), "HTTPStatus");
+        assert_eq!(type_name("foo-bar").unwrap(), "FooBar");
+        assert_eq!(
+            type_name("1foo"),
+            Err(Error::InvalidTypeName("1foo".to_string()))
+        );
+    }
+}
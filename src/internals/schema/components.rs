@@ -0,0 +1,61 @@
+use crate::internals::attrs::AnyOf;
+use crate::internals::resolver::Resolver;
+use crate::internals::schema::{
+    DocsSchema, EnumSchema, StructSchema, TryFromSchema, TypeSchema, UnionSchema,
+};
+use crate::internals::Error;
+use openapiv3::{ReferenceOr, Schema, SchemaKind, Type};
+
+/// The kind of Rust type generated for a component schema.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComponentSchema {
+    /// An object, generated as a struct.
+    Struct(StructSchema),
+    /// A string enumeration, generated as an enum.
+    Enum(EnumSchema),
+    /// A `oneOf` schema, generated as an enum with a variant for each alternative.
+    Union(UnionSchema),
+    /// Any other type, or a reference to another component, generated as a type alias documented
+    /// by the schema.
+    Alias(TypeSchema, DocsSchema),
+}
+
+impl TryFromSchema<Schema> for ComponentSchema {
+    fn try_from_schema(schema: &Schema, resolver: &Resolver) -> Result<Self, Error> {
+        let alias = |ty| Self::Alias(ty, DocsSchema::from(&schema.schema_data));
+
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::Object(obj)) if TypeSchema::is_map(obj) => {
+                TypeSchema::try_from_schema(schema, resolver).map(alias)
+            }
+            SchemaKind::Type(Type::Object(_)) | SchemaKind::AllOf { .. } => {
+                StructSchema::try_from_schema(schema, resolver).map(Self::Struct)
+            }
+            SchemaKind::Type(typ) if EnumSchema::is_enumeration(typ) => {
+                EnumSchema::try_from_schema(schema, resolver).map(Self::Enum)
+            }
+            SchemaKind::AnyOf { any_of }
+                if resolver.any_of() == AnyOf::Struct
+                    && StructSchema::is_mergeable(any_of, resolver)? =>
+            {
+                StructSchema::try_from_schema(schema, resolver).map(Self::Struct)
+            }
+            SchemaKind::OneOf { .. } | SchemaKind::AnyOf { .. } => {
+                UnionSchema::try_from_schema(schema, resolver).map(Self::Union)
+            }
+            _ => TypeSchema::try_from_schema(schema, resolver).map(alias),
+        }
+    }
+}
+
+impl TryFromSchema<ReferenceOr<Schema>> for ComponentSchema {
+    fn try_from_schema(schema: &ReferenceOr<Schema>, resolver: &Resolver) -> Result<Self, Error> {
+        match schema {
+            ReferenceOr::Reference { reference } => Ok(Self::Alias(
+                TypeSchema::Object(resolver.type_path(reference)?),
+                DocsSchema::default(),
+            )),
+            ReferenceOr::Item(schema) => Self::try_from_schema(schema, resolver),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use openapiv3::{ExternalDocumentation, SchemaData};
+
+/// The parts of a schema which document it, emitted as doc comments on the generated item.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DocsSchema {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub example: Option<serde_json::Value>,
+    pub external_docs: Option<ExternalDocumentation>,
+    pub deprecated: bool,
+}
+
+impl From<&SchemaData> for DocsSchema {
+    fn from(data: &SchemaData) -> Self {
+        Self {
+            title: data.title.clone(),
+            description: data.description.clone(),
+            example: data.example.clone(),
+            external_docs: data.external_docs.clone(),
+            deprecated: data.deprecated,
+        }
+    }
+}
@@ -1,15 +1,53 @@
+use crate::internals::attrs::Order;
+use crate::internals::resolver::Resolver;
+use crate::internals::schema::{DocsSchema, TryFromSchema};
 use crate::internals::Error;
-use openapiv3::{Schema, SchemaKind, StringType, Type};
+use openapiv3::{IntegerType, NumberType, Schema, SchemaKind, StringType, Type};
 use std::convert::TryFrom;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct VariantSchema {
     pub value: String,
+    /// The value of a variant of an integer enumeration, used as its discriminant.
+    pub discriminant: Option<i64>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct EnumSchema {
     pub variants: Vec<VariantSchema>,
+    /// Whether values other than the variants should be accepted.
+    pub open: bool,
+    pub docs: DocsSchema,
+    pub order: Order,
+}
+
+impl EnumSchema {
+    /// Whether `typ` is an enumeration which can be generated as an enum.
+    pub fn is_enumeration(typ: &Type) -> bool {
+        match typ {
+            Type::String(string) => !string.enumeration.is_empty(),
+            Type::Integer(integer) => !integer.enumeration.is_empty(),
+            Type::Number(number) => !number.enumeration.is_empty(),
+            _ => false,
+        }
+    }
+
+    fn integers(values: impl IntoIterator<Item = i64>) -> Self {
+        let variants = values
+            .into_iter()
+            .map(|value| VariantSchema {
+                value: value.to_string(),
+                discriminant: Some(value),
+            })
+            .collect();
+
+        Self {
+            variants,
+            open: false,
+            docs: DocsSchema::default(),
+            order: Order::default(),
+        }
+    }
 }
 
 impl TryFrom<&StringType> for EnumSchema {
@@ -22,23 +60,67 @@ impl TryFrom<&StringType> for EnumSchema {
                 .iter()
                 .map(|s| VariantSchema {
                     value: s.to_owned(),
+                    discriminant: None,
                 })
                 .collect();
 
-            Ok(Self { variants })
+            Ok(Self {
+                variants,
+                open: false,
+                docs: DocsSchema::default(),
+                order: Order::default(),
+            })
+        } else {
+            Err(Error::EnumNotDefined)
+        }
+    }
+}
+
+impl TryFrom<&IntegerType> for EnumSchema {
+    type Error = Error;
+
+    fn try_from(typ: &IntegerType) -> Result<Self, Error> {
+        if !typ.enumeration.is_empty() {
+            Ok(Self::integers(typ.enumeration.iter().copied()))
         } else {
             Err(Error::EnumNotDefined)
         }
     }
 }
 
-impl TryFrom<&Schema> for EnumSchema {
+impl TryFrom<&NumberType> for EnumSchema {
     type Error = Error;
 
-    fn try_from(schema: &Schema) -> Result<Self, Error> {
-        match &schema.schema_kind {
+    fn try_from(typ: &NumberType) -> Result<Self, Error> {
+        if typ.enumeration.is_empty() {
+            return Err(Error::EnumNotDefined);
+        }
+
+        // Numbers can only be used as discriminants when they are all whole.
+        if typ.enumeration.iter().any(|value| value.fract() != 0.0) {
+            return Err(Error::UnsupportedEnumType);
+        }
+
+        Ok(Self::integers(
+            typ.enumeration.iter().map(|value| *value as i64),
+        ))
+    }
+}
+
+impl TryFromSchema<Schema> for EnumSchema {
+    fn try_from_schema(schema: &Schema, resolver: &Resolver) -> Result<Self, Error> {
+        let enm = match &schema.schema_kind {
             SchemaKind::Type(Type::String(str)) => Self::try_from(str),
+            SchemaKind::Type(Type::Integer(integer)) => Self::try_from(integer),
+            SchemaKind::Type(Type::Number(number)) => Self::try_from(number),
             _ => Err(Error::UnsupportedEnumType),
-        }
+        }?;
+
+        Ok(Self {
+            open: resolver.open_enum(),
+            docs: DocsSchema::from(&schema.schema_data),
+            order: resolver.order(),
+            ..enm
+        })
     }
 }
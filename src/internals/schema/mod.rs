@@ -1,7 +1,22 @@
+mod components;
+mod docs;
 mod enums;
 mod structs;
 mod types;
+mod unions;
 
+pub use components::ComponentSchema;
+pub use docs::DocsSchema;
 pub use enums::{EnumSchema, VariantSchema};
 pub use structs::{FieldSchema, StructSchema};
-pub use types::TypeSchema;
+pub use types::{NestedSchema, TypeSchema};
+pub use unions::{UnionSchema, UnionVariantSchema};
+
+use crate::internals::resolver::Resolver;
+use crate::internals::Error;
+
+/// Fallible conversion from part of an OpenAPI document, using `resolver` to look up any `$ref`s
+/// it contains.
+pub trait TryFromSchema<T>: Sized {
+    fn try_from_schema(value: &T, resolver: &Resolver) -> Result<Self, Error>;
+}
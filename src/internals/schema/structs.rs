@@ -1,58 +1,347 @@
+use crate::internals::attrs::Order;
+use crate::internals::resolver::Resolver;
 use crate::internals::schema::types::TypeSchema;
+use crate::internals::schema::{DocsSchema, TryFromSchema};
 use crate::internals::Error;
-use openapiv3::{ObjectType, Schema, SchemaKind, Type};
-use std::collections::HashMap;
-use std::convert::TryFrom;
+use heck::SnakeCase;
+use openapiv3::{AdditionalProperties, ReferenceOr, Schema, SchemaKind, Type};
+use std::borrow::Borrow;
+use std::collections::HashSet;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FieldSchema {
     pub ty: TypeSchema,
     pub name: String,
     pub required: bool,
+    /// Whether the field being absent should be distinguished from it being `null`.
+    pub tri_state: bool,
+    /// Whether the field holds a base of an `allOf`, whose properties are flattened into the
+    /// struct when it is serialized.
+    pub flatten: bool,
+    /// The smallest and largest values the schema allows for an integer field, or for the items
+    /// of an array or map of integers, if it gives both.
+    pub bounds: Option<(i64, i64)>,
+    pub docs: DocsSchema,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct StructSchema {
     pub fields: Vec<FieldSchema>,
+    /// Whether the object forbids properties other than its fields.
+    #[cfg_attr(not(feature = "serde"), allow(unused))]
+    pub deny_unknown_fields: bool,
+    pub docs: DocsSchema,
+    pub order: Order,
 }
 
-impl TryFrom<&ObjectType> for StructSchema {
-    type Error = Error;
+/// The properties of an object, gathered from every member of any `allOf`s it is composed of.
+#[derive(Default)]
+struct Properties<'a> {
+    fields: Vec<FieldSchema>,
+    required: Vec<&'a String>,
+    /// Properties held by flattened bases, which may be marked as required by other members.
+    inherited: HashSet<String>,
+    /// Every property required by the object or any member of its `allOf`s, which a base must
+    /// require too for it to be flattened.
+    all_required: HashSet<&'a String>,
+    /// The type of any additional properties.
+    additional: Option<TypeSchema>,
+    /// Whether additional properties are forbidden.
+    deny_unknown_fields: bool,
+}
+
+impl<'a> Properties<'a> {
+    fn collect(&mut self, schema: &'a Schema, resolver: &Resolver<'a>) -> Result<(), Error> {
+        let (properties, required, additional) = match &schema.schema_kind {
+            SchemaKind::Type(Type::Object(obj)) => {
+                (&obj.properties, &obj.required, &obj.additional_properties)
+            }
+            // Members of an `allOf` often leave out the type, or only list required properties.
+            SchemaKind::Any(any) => (&any.properties, &any.required, &any.additional_properties),
+            SchemaKind::AllOf { all_of } => return self.collect_all_of(all_of, resolver),
+            _ => return Err(Error::UnsupportedStructType),
+        };
+
+        self.required.extend(required);
+
+        if let Some(additional) = additional {
+            match TypeSchema::additional_properties(additional, resolver)? {
+                Some(ty) => self.additional = Some(ty),
+                None => self.deny_unknown_fields = true,
+            }
+        }
+
+        self.collect_properties(properties, resolver)
+    }
+
+    fn collect_all_of(
+        &mut self,
+        all_of: &'a [ReferenceOr<Schema>],
+        resolver: &Resolver<'a>,
+    ) -> Result<(), Error> {
+        for (index, member) in all_of.iter().enumerate() {
+            let member_resolver = resolver.child(&["allOf", &index.to_string()]);
 
-    fn try_from(obj: &ObjectType) -> Result<Self, Error> {
-        let mut fields = HashMap::new();
+            match member {
+                ReferenceOr::Reference { reference } if resolver.flatten_all_of() => {
+                    let (base_resolver, base) = resolver.resolve(reference)?;
+                    let mut inherited = Properties {
+                        all_required: self.all_required.clone(),
+                        ..Properties::default()
+                    };
+                    inherited.collect(base, &base_resolver)?;
 
-        for (name, prop) in &obj.properties {
-            fields.insert(
-                name.clone(),
-                FieldSchema {
-                    ty: TypeSchema::try_from(prop)?,
-                    name: name.clone(),
-                    required: false,
-                },
-            );
+                    // The base's type can't hold properties which other members make required, so
+                    // they are copied into the struct instead.
+                    if inherited.relaxes(&self.all_required) {
+                        self.collect_member(member, &member_resolver)?;
+                        continue;
+                    }
+
+                    self.inherited
+                        .extend(inherited.fields.into_iter().map(|field| field.name));
+                    self.inherited.extend(inherited.inherited);
+                    self.add(FieldSchema {
+                        ty: TypeSchema::Object(resolver.type_path(reference)?),
+                        name: resolver.reference_name(reference)?.to_snake_case(),
+                        required: true,
+                        tri_state: false,
+                        flatten: true,
+                        bounds: None,
+                        docs: DocsSchema::default(),
+                    })?;
+                }
+                member => self.collect_member(member, &member_resolver)?,
+            }
         }
 
-        for required in &obj.required {
-            match fields.get_mut(required) {
+        Ok(())
+    }
+
+    fn collect_member(
+        &mut self,
+        member: &'a ReferenceOr<Schema>,
+        resolver: &Resolver<'a>,
+    ) -> Result<(), Error> {
+        match member {
+            ReferenceOr::Reference { reference } => {
+                let (resolver, schema) = resolver.resolve(reference)?;
+                self.collect(schema, &resolver)
+            }
+            ReferenceOr::Item(schema) => self.collect(schema, resolver),
+        }
+    }
+
+    fn collect_properties(
+        &mut self,
+        properties: impl IntoIterator<Item = (&'a String, &'a ReferenceOr<Box<Schema>>)>,
+        resolver: &Resolver<'a>,
+    ) -> Result<(), Error> {
+        for (name, prop) in properties {
+            let ty = TypeSchema::try_from_schema(
+                prop,
+                &resolver.member(name).child(&["properties", name]),
+            )?;
+            let docs = match prop {
+                ReferenceOr::Item(schema) => DocsSchema::from(&schema.schema_data),
+                ReferenceOr::Reference { .. } => DocsSchema::default(),
+            };
+
+            self.add(FieldSchema {
+                ty,
+                name: name.clone(),
+                required: false,
+                tri_state: false,
+                flatten: false,
+                bounds: bounds(prop, resolver),
+                docs,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether any of the properties is optional, though listed in `required`.
+    fn relaxes(&self, required: &HashSet<&String>) -> bool {
+        self.fields.iter().any(|field| {
+            !field.flatten
+                && required.contains(&field.name)
+                && !self.required.contains(&&field.name)
+        })
+    }
+
+    /// Adds a field, which may already have been declared by another member as long as both
+    /// agree on its type.
+    fn add(&mut self, field: FieldSchema) -> Result<(), Error> {
+        match self.fields.iter().find(|f| f.name == field.name) {
+            Some(existing) if existing.ty == field.ty => Ok(()),
+            Some(_) => Err(Error::ConflictingFieldTypes(field.name)),
+            None => {
+                self.fields.push(field);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Names the field holding additional properties `extra`, or `additional_properties` if a property
+/// already has that name, followed by a number if that is taken too.
+fn additional_name(fields: &[FieldSchema]) -> String {
+    let taken = |name: &str| {
+        fields
+            .iter()
+            .any(|field| field.name.to_snake_case() == name)
+    };
+
+    ["extra", "additional_properties"]
+        .iter()
+        .map(|name| name.to_string())
+        .chain((2..).map(|n| format!("additional_properties_{}", n)))
+        .find(|name| !taken(name))
+        .unwrap_or_default()
+}
+
+/// Adds the properties required by `schema` and every member of its `allOf`s to `required`.
+fn required_properties<'a>(
+    schema: &'a Schema,
+    resolver: &Resolver<'a>,
+    required: &mut HashSet<&'a String>,
+) -> Result<(), Error> {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(obj)) => required.extend(&obj.required),
+        SchemaKind::Any(any) => required.extend(&any.required),
+        SchemaKind::AllOf { all_of } => {
+            for member in all_of {
+                match member {
+                    ReferenceOr::Reference { reference } => {
+                        let (resolver, schema) = resolver.resolve(reference)?;
+                        required_properties(schema, &resolver, required)?;
+                    }
+                    ReferenceOr::Item(schema) => required_properties(schema, resolver, required)?,
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// The inclusive range of values allowed by an integer schema's `minimum` and `maximum`, looking
+/// through arrays and maps to the integers they hold.
+fn bounds<T>(schema: &ReferenceOr<T>, resolver: &Resolver) -> Option<(i64, i64)>
+where
+    T: Borrow<Schema>,
+{
+    let (resolver, schema) = match schema {
+        ReferenceOr::Reference { reference } => resolver.resolve(reference).ok()?,
+        ReferenceOr::Item(schema) => (resolver.clone(), schema.borrow()),
+    };
+
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Integer(integer)) => {
+            let minimum = match integer.minimum? {
+                minimum if integer.exclusive_minimum => minimum.checked_add(1)?,
+                minimum => minimum,
+            };
+            let maximum = match integer.maximum? {
+                maximum if integer.exclusive_maximum => maximum.checked_sub(1)?,
+                maximum => maximum,
+            };
+            Some((minimum, maximum))
+        }
+        SchemaKind::Type(Type::Array(array)) => bounds(&array.items, &resolver),
+        SchemaKind::Type(Type::Object(obj)) if TypeSchema::is_map(obj) => {
+            match &obj.additional_properties {
+                Some(AdditionalProperties::Schema(schema)) => bounds(schema.as_ref(), &resolver),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+impl TryFromSchema<Schema> for StructSchema {
+    fn try_from_schema(schema: &Schema, resolver: &Resolver) -> Result<Self, Error> {
+        let mut properties = Properties::default();
+
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::Object(_)) | SchemaKind::AllOf { .. } => {
+                if resolver.flatten_all_of() {
+                    required_properties(schema, resolver, &mut properties.all_required)?;
+                }
+                properties.collect(schema, resolver)?
+            }
+            // Any of the alternatives may be missing, so none of their properties are required.
+            SchemaKind::AnyOf { any_of } => {
+                for (index, member) in any_of.iter().enumerate() {
+                    properties
+                        .collect_member(member, &resolver.child(&["anyOf", &index.to_string()]))?;
+                }
+                properties.required.clear();
+                properties.deny_unknown_fields = false;
+            }
+            _ => return Err(Error::UnsupportedStructType),
+        }
+
+        // Additional properties are collected in a map alongside the fields.
+        if let Some(ty) = properties.additional.take() {
+            properties.add(FieldSchema {
+                ty: TypeSchema::map(ty, resolver),
+                name: additional_name(&properties.fields),
+                required: true,
+                tri_state: false,
+                flatten: true,
+                bounds: None,
+                docs: DocsSchema::default(),
+            })?;
+        }
+
+        let mut fields = properties.fields;
+
+        for required in properties.required {
+            match fields.iter_mut().find(|field| field.name == *required) {
                 Some(field) => field.required = true,
+                None if properties.inherited.contains(required) => {}
                 None => return Err(Error::UnknownField(required.to_owned())),
             }
         }
 
+        for field in &mut fields {
+            field.tri_state = resolver.tri_state()
+                && !field.required
+                && matches!(field.ty, TypeSchema::Nullable(_));
+        }
+
         Ok(Self {
-            fields: fields.drain().map(|(_, f)| f).collect(),
+            fields,
+            deny_unknown_fields: properties.deny_unknown_fields,
+            docs: DocsSchema::from(&schema.schema_data),
+            order: resolver.order(),
         })
     }
 }
 
-impl TryFrom<&Schema> for StructSchema {
-    type Error = Error;
+impl StructSchema {
+    /// Whether every alternative of an `anyOf` is an object, so that they can be merged into a
+    /// single struct.
+    pub fn is_mergeable(
+        any_of: &[ReferenceOr<Schema>],
+        resolver: &Resolver,
+    ) -> Result<bool, Error> {
+        for member in any_of {
+            let schema = match member {
+                ReferenceOr::Reference { reference } => resolver.resolve(reference)?.1,
+                ReferenceOr::Item(schema) => schema,
+            };
 
-    fn try_from(schema: &Schema) -> Result<Self, Error> {
-        match &schema.schema_kind {
-            SchemaKind::Type(Type::Object(obj)) => Self::try_from(obj),
-            _ => Err(Error::UnsupportedStructType),
+            match &schema.schema_kind {
+                SchemaKind::Type(Type::Object(_)) | SchemaKind::AllOf { .. } => {}
+                SchemaKind::Any(any) if !any.properties.is_empty() => {}
+                _ => return Ok(false),
+            }
         }
+
+        Ok(true)
     }
 }
@@ -1,47 +1,280 @@
+use crate::internals::resolver::Resolver;
+use crate::internals::schema::{ComponentSchema, EnumSchema, TryFromSchema};
 use crate::internals::Error;
 use openapiv3::{
-    ArrayType, IntegerFormat, IntegerType, NumberFormat, NumberType, ReferenceOr, Schema,
-    SchemaKind, StringFormat, StringType, Type,
+    AdditionalProperties, AnySchema, ArrayType, IntegerFormat, IntegerType, NumberFormat,
+    NumberType, ObjectType, ReferenceOr, Schema, SchemaKind, StringFormat, StringType, Type,
 };
-use std::convert::TryFrom;
+use syn::Path;
 
 /// The OpenAPI types supported by [openapi_to_rust]. This currently includes most of the formats
-/// defined in the OpenAPI Specification (except for objects which are only supported via reference)
-/// as well as sets.
-#[derive(Debug)]
+/// defined in the OpenAPI Specification as well as sets. Objects and enumerations are generated as
+/// types of their own, either as components or as [NestedSchema]s.
+#[derive(Clone, Debug, PartialEq)]
 pub enum TypeSchema {
+    /// Signed 8-bit integer.
+    Int8,
+    /// Signed 16-bit integer.
+    Int16,
     /// Signed 32-bit integer.
     Int32,
     /// Signed 64-bit integer.
     Int64,
+    /// Unsigned 8-bit integer.
+    UInt8,
+    /// Unsigned 16-bit integer.
+    UInt16,
+    /// Unsigned 32-bit integer.
+    UInt32,
+    /// Unsigned 64-bit integer.
+    UInt64,
     /// 32-bit floating-point number.
     Float,
     /// 64-bit floating-point number.
     Double,
     /// String.
     String,
-    // /// String of base64 encoded characters.
-    // Bytes,
-    // /// String of octets.
-    // Binary,
+    /// String of base64 encoded characters.
+    Bytes,
+    /// String of octets.
+    Binary,
     /// Boolean value.
     Bool,
     /// Date string conforming to RFC3339.
     Date,
     /// Date-time string conforming to RFC3339.
     DateTime,
+    /// Time string conforming to RFC3339.
+    Time,
+    /// Duration string conforming to ISO 8601.
+    Duration,
+    /// UUID string conforming to RFC 4122.
+    Uuid,
+    /// URI string conforming to RFC 3986.
+    Url,
+    /// IPv4 address in dotted-quad notation.
+    Ipv4,
+    /// IPv6 address conforming to RFC 4291.
+    Ipv6,
+    /// A type the user has given for the schema's format.
+    Custom(Path),
     /// Array of a specific type.
     Array(Box<TypeSchema>),
     /// Array of unique items of a specific type.
     Set(Box<TypeSchema>),
-    /// A reference to an object.
-    Object(String),
+    /// A reference to a component, held as the path of its Rust type.
+    Object(Path),
+    /// A type which may also be `null`.
+    Nullable(Box<TypeSchema>),
+    /// Object with arbitrary keys, each holding a value of a specific type.
+    Map(Box<TypeSchema>),
+    /// Object with arbitrary keys, kept sorted, each holding a value of a specific type.
+    OrderedMap(Box<TypeSchema>),
+    /// A reference to a component generated as a type alias, along with the type it stands for.
+    Alias(Path, Box<TypeSchema>),
+    /// Any JSON value, held as the type the user has given for such values, if any.
+    Any(Option<Path>),
+    /// An object, enumeration or composition declared inline, which is given a type of its own.
+    Nested(Box<NestedSchema>),
 }
 
-impl TryFrom<&Type> for TypeSchema {
-    type Error = Error;
+/// A schema declared inline rather than as a component.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NestedSchema {
+    /// The name of the generated type, made up of the names of the type and field it is declared
+    /// in.
+    pub name: String,
+    pub schema: ComponentSchema,
+}
+
+impl NestedSchema {
+    /// Renames the type, along with the types declared inline within it which are named after it.
+    pub fn renamed(&self, name: &str) -> Self {
+        let mut nested = self.clone();
+        nested.replace_prefix(&self.name, name);
+        nested
+    }
+
+    fn replace_prefix(&mut self, from: &str, to: &str) {
+        if let Some(rest) = self.name.strip_prefix(from) {
+            self.name = format!("{}{}", to, rest);
+        }
+
+        match &mut self.schema {
+            ComponentSchema::Struct(strct) => {
+                for field in &mut strct.fields {
+                    if let Some(nested) = field.ty.nested_mut() {
+                        nested.replace_prefix(from, to);
+                    }
+                }
+            }
+            ComponentSchema::Union(union) => {
+                for variant in &mut union.variants {
+                    if let Some(nested) = variant.ty.nested_mut() {
+                        // Untagged alternatives declared inline are named after their type.
+                        let named_after = variant.value == nested.name;
+                        nested.replace_prefix(from, to);
+                        if named_after {
+                            variant.value = nested.name.clone();
+                        }
+                    }
+                }
+            }
+            ComponentSchema::Enum(_) | ComponentSchema::Alias(..) => {}
+        }
+    }
+}
+
+impl TypeSchema {
+    /// The type of an object's additional properties, or `None` if it may not have any.
+    pub fn additional_properties(
+        additional: &AdditionalProperties,
+        resolver: &Resolver,
+    ) -> Result<Option<Self>, Error> {
+        match additional {
+            AdditionalProperties::Any(false) => Ok(None),
+            AdditionalProperties::Any(true) => Ok(Some(Self::Any(resolver.any_type().cloned()))),
+            AdditionalProperties::Schema(schema) => {
+                Self::try_from_schema(schema.as_ref(), &resolver.child(&["additionalProperties"]))
+                    .map(Some)
+            }
+        }
+    }
+
+    /// A map from strings to `value`, ordered if the resolver asks for it.
+    pub fn map(value: Self, resolver: &Resolver) -> Self {
+        if resolver.btree_map() {
+            Self::OrderedMap(Box::new(value))
+        } else {
+            Self::Map(Box::new(value))
+        }
+    }
 
-    fn try_from(value: &Type) -> Result<Self, Self::Error> {
+    /// A reference to a component. Components generated as type aliases keep the type they stand
+    /// for, as its values may need serializing in a particular way which the alias can't give.
+    pub fn reference(reference: &str, resolver: &Resolver) -> Result<Self, Error> {
+        let path = resolver.type_path(reference)?;
+
+        match resolver.alias(reference)? {
+            Some(ty) => Ok(Self::Alias(path, Box::new(ty.clone()))),
+            None => Ok(Self::Object(path)),
+        }
+    }
+
+    /// The schema declared inline in this type, such as the items of an array of objects.
+    pub fn nested(&self) -> Option<&NestedSchema> {
+        match self {
+            Self::Nested(nested) => Some(nested),
+            Self::Array(ty)
+            | Self::Set(ty)
+            | Self::Nullable(ty)
+            | Self::Map(ty)
+            | Self::OrderedMap(ty) => ty.nested(),
+            _ => None,
+        }
+    }
+
+    fn nested_mut(&mut self) -> Option<&mut NestedSchema> {
+        match self {
+            Self::Nested(nested) => Some(nested),
+            Self::Array(ty)
+            | Self::Set(ty)
+            | Self::Nullable(ty)
+            | Self::Map(ty)
+            | Self::OrderedMap(ty) => ty.nested_mut(),
+            _ => None,
+        }
+    }
+
+    /// Gives a schema declared inline a type named after the resolver's scope and member.
+    fn nested_from(schema: &Schema, resolver: &Resolver) -> Result<Self, Error> {
+        let name = resolver.nested_name();
+        let schema = ComponentSchema::try_from_schema(schema, &resolver.scoped(&name))?;
+
+        Ok(Self::Nested(Box::new(NestedSchema { name, schema })))
+    }
+
+    /// The type of an integer with a sized format, such as `uint8`. Otherwise, if the resolver
+    /// asks for it, integers with a `minimum` of zero or more are given the smallest unsigned type
+    /// which holds their `maximum`.
+    fn integer(integer: &IntegerType, resolver: &Resolver) -> Self {
+        use openapiv3::VariantOrUnknownOrEmpty::*;
+
+        let signed = match &integer.format {
+            Item(IntegerFormat::Int64) => Self::Int64,
+            Unknown(format) => match format.as_str() {
+                "int8" => return Self::Int8,
+                "int16" => return Self::Int16,
+                "uint8" => return Self::UInt8,
+                "uint16" => return Self::UInt16,
+                "uint32" => return Self::UInt32,
+                "uint64" => return Self::UInt64,
+                "int64" => Self::Int64,
+                _ => Self::Int32,
+            },
+            Item(IntegerFormat::Int32) | Empty => Self::Int32,
+        };
+
+        if !resolver.infer_unsigned() || !matches!(integer.minimum, Some(minimum) if minimum >= 0) {
+            return signed;
+        }
+
+        let maximum = match integer.maximum {
+            Some(maximum) if integer.exclusive_maximum => Some(maximum.saturating_sub(1)),
+            maximum => maximum,
+        };
+
+        match maximum {
+            Some(maximum) if maximum <= i64::from(u8::MAX) => Self::UInt8,
+            Some(maximum) if maximum <= i64::from(u16::MAX) => Self::UInt16,
+            Some(maximum) if maximum <= i64::from(u32::MAX) => Self::UInt32,
+            _ if signed == Self::Int32 => Self::UInt32,
+            _ => Self::UInt64,
+        }
+    }
+
+    /// Whether `obj` only has additional properties, and so is a map rather than a struct.
+    pub fn is_map(obj: &ObjectType) -> bool {
+        obj.properties.is_empty()
+            && matches!(
+                obj.additional_properties,
+                Some(AdditionalProperties::Any(true)) | Some(AdditionalProperties::Schema(_))
+            )
+    }
+}
+
+/// The format of a type, as it is written in the schema.
+fn format_name(typ: &Type) -> Option<&str> {
+    use openapiv3::VariantOrUnknownOrEmpty::*;
+
+    match typ {
+        Type::String(StringType { format, .. }) => match format {
+            Item(StringFormat::Date) => Some("date"),
+            Item(StringFormat::DateTime) => Some("date-time"),
+            Item(StringFormat::Password) => Some("password"),
+            Item(StringFormat::Byte) => Some("byte"),
+            Item(StringFormat::Binary) => Some("binary"),
+            Unknown(format) => Some(format),
+            Empty => None,
+        },
+        Type::Number(NumberType { format, .. }) => match format {
+            Item(NumberFormat::Float) => Some("float"),
+            Item(NumberFormat::Double) => Some("double"),
+            Unknown(format) => Some(format),
+            Empty => None,
+        },
+        Type::Integer(IntegerType { format, .. }) => match format {
+            Item(IntegerFormat::Int32) => Some("int32"),
+            Item(IntegerFormat::Int64) => Some("int64"),
+            Unknown(format) => Some(format),
+            Empty => None,
+        },
+        Type::Object(_) | Type::Array(_) | Type::Boolean { .. } => None,
+    }
+}
+
+impl TryFromSchema<Type> for TypeSchema {
+    fn try_from_schema(value: &Type, resolver: &Resolver) -> Result<Self, Error> {
         use openapiv3::VariantOrUnknownOrEmpty::*;
 
         match &value {
@@ -53,52 +286,133 @@ impl TryFrom<&Type> for TypeSchema {
                 format: Item(StringFormat::DateTime),
                 ..
             }) => Ok(Self::DateTime),
+            Type::String(StringType {
+                format: Item(StringFormat::Byte),
+                ..
+            }) => Ok(Self::Bytes),
+            Type::String(StringType {
+                format: Item(StringFormat::Binary),
+                ..
+            }) => Ok(Self::Binary),
+            Type::String(StringType {
+                format: Unknown(format),
+                ..
+            }) if format == "time" => Ok(Self::Time),
+            Type::String(StringType {
+                format: Unknown(format),
+                ..
+            }) if format == "duration" => Ok(Self::Duration),
+            Type::String(StringType {
+                format: Unknown(format),
+                ..
+            }) if format == "uuid" && cfg!(feature = "uuid") => Ok(Self::Uuid),
+            Type::String(StringType {
+                format: Unknown(format),
+                ..
+            }) if (format == "uri" || format == "url") && cfg!(feature = "url") => Ok(Self::Url),
+            Type::String(StringType {
+                format: Unknown(format),
+                ..
+            }) if format == "ipv4" && cfg!(feature = "ip") => Ok(Self::Ipv4),
+            Type::String(StringType {
+                format: Unknown(format),
+                ..
+            }) if format == "ipv6" && cfg!(feature = "ip") => Ok(Self::Ipv6),
             Type::String(_) => Ok(Self::String),
             Type::Number(NumberType {
                 format: Item(NumberFormat::Double),
                 ..
             }) => Ok(Self::Double),
             Type::Number(_) => Ok(Self::Float),
-            Type::Integer(IntegerType {
-                format: Item(IntegerFormat::Int64),
+            Type::Integer(integer) => Ok(Self::integer(integer, resolver)),
+            Type::Object(ObjectType {
+                properties,
+                additional_properties: Some(additional),
                 ..
-            }) => Ok(Self::Int64),
-            Type::Integer(_) => Ok(Self::Int32),
+            }) if properties.is_empty() => {
+                match Self::additional_properties(additional, resolver)? {
+                    Some(value) => Ok(Self::map(value, resolver)),
+                    None => Err(Error::UnsupportedNestedObjectType),
+                }
+            }
             Type::Object(_) => Err(Error::UnsupportedNestedObjectType),
             Type::Array(ArrayType {
                 items,
                 unique_items: true,
                 ..
-            }) => Ok(Self::Set(Box::new(Self::try_from(items)?))),
-            Type::Array(ArrayType { items, .. }) => {
-                Ok(Self::Array(Box::new(Self::try_from(items)?)))
-            }
+            }) => Ok(Self::Set(Box::new(Self::try_from_schema(
+                items,
+                &resolver.child(&["items"]),
+            )?))),
+            Type::Array(ArrayType { items, .. }) => Ok(Self::Array(Box::new(
+                Self::try_from_schema(items, &resolver.child(&["items"]))?,
+            ))),
             Type::Boolean { .. } => Ok(Self::Bool),
         }
     }
 }
 
-impl TryFrom<&Schema> for TypeSchema {
-    type Error = Error;
+impl TryFromSchema<Schema> for TypeSchema {
+    fn try_from_schema(value: &Schema, resolver: &Resolver) -> Result<Self, Error> {
+        // Enumerations are generated even if their format is mapped to a type.
+        let format = match &value.schema_kind {
+            SchemaKind::Type(typ) if !EnumSchema::is_enumeration(typ) => format_name(typ),
+            _ => None,
+        };
+
+        // A type given by the user replaces the generated one.
+        let ty = match resolver.custom_type(format)? {
+            Some(path) => Self::Custom(path),
+            None => match &value.schema_kind {
+                SchemaKind::Type(Type::Object(obj)) if !Self::is_map(obj) => {
+                    Self::nested_from(value, resolver)?
+                }
+                SchemaKind::Type(typ) if EnumSchema::is_enumeration(typ) => {
+                    Self::nested_from(value, resolver)?
+                }
+                SchemaKind::Type(typ) => Self::try_from_schema(typ, resolver)?,
+                // A lone reference is wrapped in an `allOf` to give it a description or make it
+                // nullable, as siblings of `$ref` are ignored.
+                SchemaKind::AllOf { all_of } => match all_of.as_slice() {
+                    [ReferenceOr::Reference { reference }] => Self::reference(reference, resolver)?,
+                    _ => Self::nested_from(value, resolver)?,
+                },
+                SchemaKind::OneOf { .. } | SchemaKind::AnyOf { .. } => {
+                    Self::nested_from(value, resolver)?
+                }
+                // An empty schema places no restrictions on the value.
+                SchemaKind::Any(any) if *any == AnySchema::default() => {
+                    Self::Any(resolver.any_type().cloned())
+                }
+                SchemaKind::Any(_) => return Err(Error::UnsupportedSchemaType("any".to_string())),
+            },
+        };
 
-    fn try_from(value: &Schema) -> Result<Self, Self::Error> {
-        match &value.schema_kind {
-            SchemaKind::Type(typ) => Self::try_from(typ),
-            SchemaKind::OneOf { .. } => Err(Error::UnsupportedSchemaType("oneOf".to_string())),
-            SchemaKind::AllOf { .. } => Err(Error::UnsupportedSchemaType("allOf".to_string())),
-            SchemaKind::AnyOf { .. } => Err(Error::UnsupportedSchemaType("anyOf".to_string())),
-            SchemaKind::Any(_) => Err(Error::UnsupportedSchemaType("any".to_string())),
+        if value.schema_data.nullable {
+            Ok(Self::Nullable(Box::new(ty)))
+        } else {
+            Ok(ty)
         }
     }
 }
 
-impl TryFrom<&ReferenceOr<Box<Schema>>> for TypeSchema {
-    type Error = Error;
+impl TryFromSchema<ReferenceOr<Box<Schema>>> for TypeSchema {
+    fn try_from_schema(
+        value: &ReferenceOr<Box<Schema>>,
+        resolver: &Resolver,
+    ) -> Result<Self, Error> {
+        match &value {
+            ReferenceOr::Reference { reference } => Self::reference(reference, resolver),
+            ReferenceOr::Item(schema) => Self::try_from_schema(schema.as_ref(), resolver),
+        }
+    }
+}
 
-    fn try_from(value: &ReferenceOr<Box<Schema>>) -> Result<Self, Self::Error> {
+impl TryFromSchema<ReferenceOr<Schema>> for TypeSchema {
+    fn try_from_schema(value: &ReferenceOr<Schema>, resolver: &Resolver) -> Result<Self, Error> {
         match &value {
-            ReferenceOr::Reference { reference } => Ok(Self::Object(reference.to_owned())),
-            ReferenceOr::Item(schema) => Self::try_from(schema.as_ref()),
+            ReferenceOr::Reference { reference } => Self::reference(reference, resolver),
+            ReferenceOr::Item(schema) => Self::try_from_schema(schema, resolver),
         }
     }
 }
@@ -0,0 +1,245 @@
+use crate::internals::attrs::Order;
+use crate::internals::resolver::Resolver;
+use crate::internals::schema::{ComponentSchema, DocsSchema, TryFromSchema, TypeSchema};
+use crate::internals::Error;
+use openapiv3::{Discriminator, ReferenceOr, Schema, SchemaKind, Type};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+/// One of the alternatives of a `oneOf` or `anyOf` schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnionVariantSchema {
+    /// The discriminator value of the alternative, or a name derived from its type when the
+    /// schema has no discriminator.
+    pub value: String,
+    pub ty: TypeSchema,
+    /// Whether the alternative declares the discriminator property itself, so it holds the value
+    /// rather than leaving it to the union.
+    pub declares_tag: bool,
+    /// The documentation of an alternative declared inline.
+    pub docs: DocsSchema,
+}
+
+/// A `oneOf` or `anyOf` schema, generated as an enum with a tuple variant for each alternative.
+/// Without a discriminator the alternatives are ordered from the most to the least specific, as
+/// the first one a value matches is used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnionSchema {
+    /// The name of the property holding the discriminator value, if the schema has one.
+    pub tag: Option<String>,
+    pub variants: Vec<UnionVariantSchema>,
+    pub docs: DocsSchema,
+    /// The order of alternatives with a discriminator. Those without one are ordered by how
+    /// specific they are.
+    pub order: Order,
+}
+
+impl TryFromSchema<Schema> for UnionSchema {
+    fn try_from_schema(schema: &Schema, resolver: &Resolver) -> Result<Self, Error> {
+        let (keyword, alternatives) = match &schema.schema_kind {
+            SchemaKind::OneOf { one_of } => ("oneOf", one_of),
+            SchemaKind::AnyOf { any_of } => ("anyOf", any_of),
+            _ => return Err(Error::UnsupportedEnumType),
+        };
+
+        let discriminator = schema.schema_data.discriminator.as_ref();
+        let mut values = HashSet::new();
+        let mut variants = vec![];
+
+        for (index, alternative) in alternatives.iter().enumerate() {
+            let (value, ty, declares_tag) = match (alternative, discriminator) {
+                (ReferenceOr::Reference { reference }, Some(discriminator)) => (
+                    discriminator_value(discriminator, reference, resolver)?,
+                    TypeSchema::reference(reference, resolver)?,
+                    declares(reference, &discriminator.property_name, resolver)?,
+                ),
+                (ReferenceOr::Item(_), Some(_)) => return Err(Error::UnsupportedInlineVariant),
+                (ReferenceOr::Reference { reference }, None) => {
+                    let ty = TypeSchema::reference(reference, resolver)?;
+                    (variant_name(&ty), ty, false)
+                }
+                (ReferenceOr::Item(schema), None) => {
+                    let member = format!("Variant{}", index + 1);
+                    let resolver = resolver
+                        .member(&member)
+                        .child(&[keyword, &index.to_string()]);
+                    let ty = TypeSchema::try_from_schema(schema, &resolver)?;
+                    (variant_name(&ty), ty, false)
+                }
+            };
+
+            if !values.insert(value.clone()) {
+                return Err(Error::DuplicateVariant(value));
+            }
+
+            let docs = match alternative {
+                ReferenceOr::Item(schema) => DocsSchema::from(&schema.schema_data),
+                ReferenceOr::Reference { .. } => DocsSchema::default(),
+            };
+
+            variants.push(UnionVariantSchema {
+                value,
+                ty,
+                declares_tag,
+                docs,
+            });
+        }
+
+        if discriminator.is_none() {
+            let mut ranked = alternatives
+                .iter()
+                .zip(variants)
+                .map(|(alternative, variant)| {
+                    Ok((specificity(alternative, &variant.ty, resolver)?, variant))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            ranked.sort_by_key(|(rank, _)| *rank);
+            variants = ranked.into_iter().map(|(_, variant)| variant).collect();
+        }
+
+        Ok(Self {
+            tag: discriminator.map(|discriminator| discriminator.property_name.clone()),
+            variants,
+            docs: DocsSchema::from(&schema.schema_data),
+            order: resolver.order(),
+        })
+    }
+}
+
+/// Returns the value identifying the component `reference` points at. This is the key of its
+/// entry in the discriminator's mapping, or the name of the component if it isn't mapped.
+fn discriminator_value(
+    discriminator: &Discriminator,
+    reference: &str,
+    resolver: &Resolver,
+) -> Result<String, Error> {
+    let name = resolver.reference_name(reference)?;
+
+    for (value, target) in &discriminator.mapping {
+        // Mapping targets may be either references or plain component names.
+        let target = if target.contains('#') {
+            resolver.reference_name(target)?
+        } else {
+            target.clone()
+        };
+
+        if target == name {
+            return Ok(value.clone());
+        }
+    }
+
+    Ok(name)
+}
+
+/// Whether the component `reference` points at declares `property`, either itself or in any
+/// member of an `allOf`.
+fn declares(reference: &str, property: &str, resolver: &Resolver) -> Result<bool, Error> {
+    let (resolver, schema) = resolver.resolve(reference)?;
+    declares_property(schema, property, &resolver)
+}
+
+fn declares_property(schema: &Schema, property: &str, resolver: &Resolver) -> Result<bool, Error> {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(obj)) => Ok(obj.properties.contains_key(property)),
+        SchemaKind::Any(any) => Ok(any.properties.contains_key(property)),
+        SchemaKind::AllOf { all_of } => {
+            for member in all_of {
+                let declared = match member {
+                    ReferenceOr::Reference { reference } => {
+                        declares(reference, property, resolver)?
+                    }
+                    ReferenceOr::Item(schema) => declares_property(schema, property, resolver)?,
+                };
+                if declared {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Ranks an alternative without a discriminator, lower being more specific. Components come
+/// first, ordered by how many properties they require, followed by the inline types which accept
+/// the fewest values.
+fn specificity(
+    alternative: &ReferenceOr<Schema>,
+    ty: &TypeSchema,
+    resolver: &Resolver,
+) -> Result<(u8, Reverse<usize>), Error> {
+    fn rank(ty: &TypeSchema) -> u8 {
+        match ty {
+            TypeSchema::Object(_) | TypeSchema::Alias(..) => 0,
+            TypeSchema::Array(_) | TypeSchema::Set(_) => 1,
+            TypeSchema::Map(_) | TypeSchema::OrderedMap(_) => 1,
+            TypeSchema::Bool => 2,
+            TypeSchema::Int8 | TypeSchema::Int16 | TypeSchema::Int32 => 3,
+            TypeSchema::UInt8 | TypeSchema::UInt16 | TypeSchema::UInt32 => 3,
+            TypeSchema::Int64 | TypeSchema::UInt64 => 4,
+            TypeSchema::Float => 5,
+            TypeSchema::Double => 6,
+            TypeSchema::Date | TypeSchema::DateTime => 7,
+            TypeSchema::Time | TypeSchema::Duration => 7,
+            TypeSchema::Bytes | TypeSchema::Binary => 7,
+            TypeSchema::Uuid | TypeSchema::Url | TypeSchema::Ipv4 | TypeSchema::Ipv6 => 7,
+            TypeSchema::Custom(_) => 7,
+            TypeSchema::String => 8,
+            TypeSchema::Nullable(ty) => rank(ty),
+            TypeSchema::Any(_) => 9,
+            TypeSchema::Nested(nested) => match nested.schema {
+                ComponentSchema::Struct(_) => 0,
+                ComponentSchema::Enum(_) => 8,
+                _ => 9,
+            },
+        }
+    }
+
+    let required = match alternative {
+        ReferenceOr::Reference { reference } => match &resolver.resolve(reference)?.1.schema_kind {
+            SchemaKind::Type(Type::Object(obj)) => obj.required.len(),
+            _ => 0,
+        },
+        ReferenceOr::Item(_) => 0,
+    };
+
+    Ok((rank(ty), Reverse(required)))
+}
+
+/// Names an alternative without a discriminator after its type.
+fn variant_name(ty: &TypeSchema) -> String {
+    match ty {
+        TypeSchema::Int8
+        | TypeSchema::Int16
+        | TypeSchema::Int32
+        | TypeSchema::Int64
+        | TypeSchema::UInt8
+        | TypeSchema::UInt16
+        | TypeSchema::UInt32
+        | TypeSchema::UInt64 => "Integer".to_string(),
+        TypeSchema::Float | TypeSchema::Double => "Number".to_string(),
+        TypeSchema::String => "String".to_string(),
+        TypeSchema::Bool => "Bool".to_string(),
+        TypeSchema::Date => "Date".to_string(),
+        TypeSchema::DateTime => "DateTime".to_string(),
+        TypeSchema::Time => "Time".to_string(),
+        TypeSchema::Duration => "Duration".to_string(),
+        TypeSchema::Bytes => "Bytes".to_string(),
+        TypeSchema::Binary => "Binary".to_string(),
+        TypeSchema::Array(_) => "Array".to_string(),
+        TypeSchema::Set(_) => "Set".to_string(),
+        TypeSchema::Uuid => "Uuid".to_string(),
+        TypeSchema::Url => "Url".to_string(),
+        TypeSchema::Ipv4 => "Ipv4".to_string(),
+        TypeSchema::Ipv6 => "Ipv6".to_string(),
+        TypeSchema::Object(path) | TypeSchema::Custom(path) | TypeSchema::Alias(path, _) => path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+            .unwrap_or_default(),
+        TypeSchema::Nullable(ty) => variant_name(ty),
+        TypeSchema::Map(_) | TypeSchema::OrderedMap(_) => "Map".to_string(),
+        TypeSchema::Any(_) => "Any".to_string(),
+        TypeSchema::Nested(nested) => nested.name.clone(),
+    }
+}
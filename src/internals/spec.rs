@@ -0,0 +1,387 @@
+use crate::internals::error::Error;
+use crate::internals::resolver::{child_pointer, component_pointer, split_reference};
+use crate::internals::utils::{document_from_file, normalize_path, resolve_path};
+use openapiv3::{AdditionalProperties, OpenAPI, ReferenceOr, Schema, SchemaKind, Type};
+use proc_macro2::TokenStream;
+use quote::quote;
+use serde_json::Value;
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// Specs which have already been loaded by this compiler process, keyed by the canonical path of
+/// their root document. Every macro invocation referring to the same file shares one parsed copy.
+fn cache() -> &'static Mutex<HashMap<PathBuf, Arc<Spec>>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, Arc<Spec>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Identifies the version of a file on disk, so that cached specs can be discarded once one of
+/// their documents is modified.
+#[derive(Debug, Eq, PartialEq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        std::fs::metadata(path).ok().map(|metadata| Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+/// An OpenAPI document along with every document it references through external `$ref`s.
+#[derive(Debug)]
+pub struct Spec {
+    root: PathBuf,
+    documents: HashMap<PathBuf, OpenAPI>,
+    stamps: Vec<(PathBuf, Option<FileStamp>)>,
+    /// The `x-rust-type` extensions of the schemas in the documents, keyed by the path of the
+    /// document and the JSON pointer to the schema within it.
+    rust_types: HashMap<(PathBuf, String), String>,
+}
+
+impl Spec {
+    /// Loads the document at `path` and, transitively, the documents it references. Specs are
+    /// cached for the lifetime of the process and only parsed again when one of their files
+    /// changes.
+    pub fn load(path: &str) -> Result<Arc<Self>, Error> {
+        let root = resolve_path(path)?;
+        let key = std::fs::canonicalize(&root).unwrap_or_else(|_| root.clone());
+
+        let mut cache = cache().lock().unwrap_or_else(|err| err.into_inner());
+
+        match cache.get(&key) {
+            Some(spec) if spec.is_fresh() => Ok(spec.clone()),
+            _ => {
+                let spec = Arc::new(Self::parse(root)?);
+                cache.insert(key, spec.clone());
+                Ok(spec)
+            }
+        }
+    }
+
+    fn parse(root: PathBuf) -> Result<Self, Error> {
+        let mut documents = HashMap::new();
+        let mut stamps = vec![];
+        // Referenced documents which can't be loaded are only reported if a type is generated
+        // from one of them.
+        let mut failed = HashSet::new();
+        let mut rust_types = HashMap::new();
+        let mut pending = vec![root.clone()];
+
+        while let Some(path) = pending.pop() {
+            if documents.contains_key(&path) || failed.contains(&path) {
+                continue;
+            }
+
+            // The stamp is taken before reading so a concurrent edit invalidates the cache.
+            stamps.push((path.clone(), FileStamp::of(&path)));
+            let (openapi, raw) = match document_from_file(&path) {
+                Ok(document) => document,
+                Err(_) if path != root => {
+                    failed.insert(path);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+
+            // Only references from schemas are followed, as those are the only ones types are
+            // generated from.
+            let mut references = vec![];
+            if let Some(components) = &openapi.components {
+                let schemas = serde_json::to_value(&components.schemas).unwrap_or_default();
+                collect_references(&schemas, &mut references);
+
+                for (name, schema) in &components.schemas {
+                    let raw = raw
+                        .pointer("/components/schemas")
+                        .and_then(|raw| raw.get(name));
+                    if let (ReferenceOr::Item(schema), Some(raw)) = (schema, raw) {
+                        let mut collector = RustTypes {
+                            document: &path,
+                            rust_types: &mut rust_types,
+                        };
+                        collector.collect(raw, schema, &component_pointer(name));
+                    }
+                }
+            }
+
+            // References which can't be followed, such as remote ones, are only reported if a
+            // type is generated from the schema containing them.
+            for reference in references {
+                if let Ok((Some(file), _)) = split_reference(&reference) {
+                    pending.push(external_path(&path, file));
+                }
+            }
+
+            documents.insert(path, openapi);
+        }
+
+        Ok(Self {
+            root,
+            documents,
+            stamps,
+            rust_types,
+        })
+    }
+
+    /// Whether none of the spec's documents have changed since it was loaded.
+    fn is_fresh(&self) -> bool {
+        self.stamps
+            .iter()
+            .all(|(path, stamp)| FileStamp::of(path) == *stamp)
+    }
+
+    /// The path of the document the spec was loaded from.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Looks up the document at `path`, returning the path as stored in the spec.
+    pub fn document(&self, path: &Path) -> Option<(&Path, &OpenAPI)> {
+        self.documents
+            .get_key_value(path)
+            .map(|(path, openapi)| (path.as_path(), openapi))
+    }
+
+    /// Why the document at `path`, which is referenced but isn't part of the spec, can't be loaded.
+    pub fn load_error(&self, path: &Path) -> Option<Error> {
+        document_from_file(path).err()
+    }
+
+    /// The Rust type given by the `x-rust-type` extension of the schema at `pointer` in the
+    /// document at `document`, if it has one.
+    pub fn rust_type(&self, document: &Path, pointer: &str) -> Option<&str> {
+        self.rust_types
+            .get(&(document.to_path_buf(), pointer.to_string()))
+            .map(String::as_str)
+    }
+
+    /// Tokens which make the compiler treat every document in the spec as an input of the crate,
+    /// so that editing any of them triggers a rebuild.
+    pub fn dependency_tokens(&self) -> TokenStream {
+        let mut paths: Vec<_> = self
+            .documents
+            .keys()
+            .map(|path| path.to_string_lossy())
+            .collect();
+        paths.sort();
+
+        quote! {
+            #(const _: &[u8] = include_bytes!(#paths);)*
+        }
+    }
+}
+
+/// Returns the path of a file referenced from the document at `document`.
+pub fn external_path(document: &Path, file: &str) -> PathBuf {
+    normalize_path(
+        &document
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(file),
+    )
+}
+
+/// Records the `x-rust-type` extensions of the schemas in one document.
+struct RustTypes<'a> {
+    document: &'a Path,
+    rust_types: &'a mut HashMap<(PathBuf, String), String>,
+}
+
+impl RustTypes<'_> {
+    /// Records the extensions of `schema`, found at `pointer`, and of any schemas declared inline
+    /// in it, whose plain value is `raw`.
+    fn collect(&mut self, raw: &Value, schema: &Schema, pointer: &str) {
+        if let Some(rust_type) = raw.get("x-rust-type").and_then(Value::as_str) {
+            self.rust_types.insert(
+                (self.document.to_path_buf(), pointer.to_string()),
+                rust_type.to_string(),
+            );
+        }
+
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::Object(obj)) => {
+                self.collect_properties(raw, &obj.properties, pointer);
+                self.collect_additional(raw, &obj.additional_properties, pointer);
+            }
+            SchemaKind::Type(Type::Array(array)) => self.collect_inline(
+                raw.get("items"),
+                &array.items,
+                &child_pointer(pointer, &["items"]),
+            ),
+            SchemaKind::Type(_) => {}
+            SchemaKind::Any(any) => {
+                self.collect_properties(raw, &any.properties, pointer);
+                self.collect_additional(raw, &any.additional_properties, pointer);
+                if let Some(items) = &any.items {
+                    let items_pointer = child_pointer(pointer, &["items"]);
+                    self.collect_inline(raw.get("items"), items, &items_pointer);
+                }
+            }
+            SchemaKind::AllOf { all_of } => self.collect_members(raw, "allOf", all_of, pointer),
+            SchemaKind::OneOf { one_of } => self.collect_members(raw, "oneOf", one_of, pointer),
+            SchemaKind::AnyOf { any_of } => self.collect_members(raw, "anyOf", any_of, pointer),
+        }
+    }
+
+    fn collect_properties<'a>(
+        &mut self,
+        raw: &Value,
+        properties: impl IntoIterator<Item = (&'a String, &'a ReferenceOr<Box<Schema>>)>,
+        pointer: &str,
+    ) {
+        for (name, property) in properties {
+            let raw = raw.get("properties").and_then(|raw| raw.get(name));
+            let pointer = child_pointer(pointer, &["properties", name]);
+            self.collect_inline(raw, property, &pointer);
+        }
+    }
+
+    fn collect_additional(
+        &mut self,
+        raw: &Value,
+        additional: &Option<AdditionalProperties>,
+        pointer: &str,
+    ) {
+        if let Some(AdditionalProperties::Schema(schema)) = additional {
+            let pointer = child_pointer(pointer, &["additionalProperties"]);
+            self.collect_inline(raw.get("additionalProperties"), schema.as_ref(), &pointer);
+        }
+    }
+
+    fn collect_members(
+        &mut self,
+        raw: &Value,
+        keyword: &str,
+        members: &[ReferenceOr<Schema>],
+        pointer: &str,
+    ) {
+        for (index, member) in members.iter().enumerate() {
+            let raw = raw.get(keyword).and_then(|raw| raw.get(index));
+            let pointer = child_pointer(pointer, &[keyword, &index.to_string()]);
+            self.collect_inline(raw, member, &pointer);
+        }
+    }
+
+    fn collect_inline<T: Borrow<Schema>>(
+        &mut self,
+        raw: Option<&Value>,
+        schema: &ReferenceOr<T>,
+        pointer: &str,
+    ) {
+        if let (Some(raw), ReferenceOr::Item(schema)) = (raw, schema) {
+            self.collect(raw, schema.borrow(), pointer);
+        }
+    }
+}
+
+fn collect_references(value: &Value, references: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => references.push(reference.clone()),
+                    _ => collect_references(value, references),
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                collect_references(value, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internals::spec::{external_path, Spec};
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    const MINIMAL_SCHEMA: &str = "openapi: 3.0.0\ninfo: {title: Test, version: 0.0.0}\npaths: {}\n";
+
+    #[test]
+    fn test_cache() {
+        let path =
+            std::env::temp_dir().join(format!("openapi_to_rust_{}.yaml", std::process::id()));
+        let path_str = path.to_str().unwrap();
+
+        std::fs::write(&path, MINIMAL_SCHEMA).unwrap();
+        let first = Spec::load(path_str).unwrap();
+        let second = Spec::load(path_str).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        std::fs::write(&path, format!("{}components: {{}}\n", MINIMAL_SCHEMA)).unwrap();
+        let third = Spec::load(path_str).unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert!(third.document(&path).unwrap().1.components.is_some());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_external_documents() {
+        let spec = Spec::load("./test-resources/test_schema.yaml").unwrap();
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-resources");
+
+        assert!(spec.document(&dir.join("test_schema.yaml")).is_some());
+        assert!(spec.document(&dir.join("external_schema.yaml")).is_some());
+        assert!(spec.document(&dir.join("test_schema.json")).is_none());
+
+        let dependencies = spec.dependency_tokens().to_string();
+        for file in &["external_schema.yaml", "test_schema.yaml"] {
+            let path = dir.join(file).display().to_string();
+            assert!(dependencies.contains(&format!("include_bytes ! ({:?})", path)));
+        }
+    }
+
+    #[test]
+    fn test_external_path() {
+        assert_eq!(
+            external_path(Path::new("/specs/api/root.yaml"), "../common/./types.yaml"),
+            PathBuf::from("/specs/common/types.yaml")
+        );
+        assert_eq!(
+            external_path(Path::new("/specs/root.yaml"), "types.yaml"),
+            PathBuf::from("/specs/types.yaml")
+        );
+    }
+
+    #[test]
+    fn test_rust_types() {
+        let spec = Spec::load("./test-resources/test_schema.yaml").unwrap();
+        let document = spec.root();
+
+        assert_eq!(
+            spec.rust_type(document, "/components/schemas/TestMoney"),
+            Some("crate::Decimal")
+        );
+        assert_eq!(
+            spec.rust_type(
+                document,
+                "/components/schemas/TestRustTypes/properties/tags/items"
+            ),
+            Some("crate::Tag")
+        );
+        assert_eq!(
+            spec.rust_type(
+                document,
+                "/components/schemas/TestRustTypesDerived/allOf/1/properties/discount"
+            ),
+            Some("crate::Decimal")
+        );
+        assert_eq!(
+            spec.rust_type(document, "/components/schemas/TestRustTypes"),
+            None
+        );
+    }
+}
@@ -1,9 +1,148 @@
 use crate::internals::error::Error;
 use openapiv3::OpenAPI;
+use serde_json::Value;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 
-pub fn openapi_from_file(path: &str) -> Result<OpenAPI, Error> {
-    let file = std::fs::File::open(path).map_err(|_| Error::FileOpenFailed(path.to_string()))?;
-    serde_yaml::from_reader(file).map_err(|_| Error::FileReadFailed(path.to_string()))
+/// The serialization formats an OpenAPI document can be written in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    Json,
+    Yaml,
+}
+
+impl Format {
+    /// Determines the format of a document from its file extension, falling back to inspecting
+    /// its contents when the extension is missing or unrecognised.
+    fn detect(path: &Path, contents: &str) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Format::Json,
+            Some(ext) if ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml") => {
+                Format::Yaml
+            }
+            _ => match contents.trim_start().chars().next() {
+                Some('{') => Format::Json,
+                _ => Format::Yaml,
+            },
+        }
+    }
+
+    /// Parses a document both as an OpenAPI document and as a plain value.
+    fn parse(self, contents: &str) -> Result<(OpenAPI, Value), String> {
+        match self {
+            Format::Json => Ok((
+                serde_json::from_str(contents).map_err(|err| err.to_string())?,
+                serde_json::from_str(contents).map_err(|err| err.to_string())?,
+            )),
+            Format::Yaml => Ok((
+                serde_yaml::from_str(contents).map_err(|err| err.to_string())?,
+                serde_yaml::from_str(contents).map_err(|err| err.to_string())?,
+            )),
+        }
+    }
+}
+
+/// Reads the document at `path`, both as an OpenAPI document and as a plain value, which keeps the
+/// vendor extensions the former drops.
+pub fn document_from_file(path: &Path) -> Result<(OpenAPI, Value), Error> {
+    let display = path.display().to_string();
+
+    let mut file = std::fs::File::open(path)
+        .map_err(|err| Error::FileOpenFailed(display.clone(), err.to_string()))?;
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .map_err(|_| Error::FileReadFailed(display.clone()))?;
+
+    Format::detect(path, &contents)
+        .parse(&contents)
+        .map_err(|err| Error::FileParseFailed(display, err))
+}
+
+/// Expands any environment variables in `path` and makes it absolute. Relative paths are resolved
+/// against the directory containing the manifest of the crate being compiled, rather than the
+/// compiler's working directory which varies between workspaces and tools.
+pub fn resolve_path(path: &str) -> Result<PathBuf, Error> {
+    let path = PathBuf::from(expand_env_vars(path)?);
+
+    let path = if path.is_absolute() {
+        path
+    } else {
+        match std::env::var_os("CARGO_MANIFEST_DIR") {
+            Some(dir) => PathBuf::from(dir).join(path),
+            None => std::env::current_dir()
+                .map(|dir| dir.join(&path))
+                .unwrap_or(path),
+        }
+    };
+
+    Ok(normalize_path(&path))
+}
+
+/// Removes the `.` and `..` segments from `path` without touching the file system, so that every
+/// way of referring to a document gives the same path.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => normalized.push(component),
+            },
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Replaces `$NAME` and `${NAME}` with the value of the environment variable `NAME`. A literal `$`
+/// can be written as `$$`.
+fn expand_env_vars(s: &str) -> Result<String, Error> {
+    let mut expanded = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+
+        let name: String = match chars.peek() {
+            Some('$') => {
+                chars.next();
+                expanded.push('$');
+                continue;
+            }
+            Some('{') => {
+                chars.next();
+                chars.by_ref().take_while(|c| *c != '}').collect()
+            }
+            _ => {
+                let mut name = String::new();
+                while let Some(c) = chars
+                    .peek()
+                    .filter(|c| c.is_ascii_alphanumeric() || **c == '_')
+                {
+                    name.push(*c);
+                    chars.next();
+                }
+                name
+            }
+        };
+
+        match std::env::var(&name) {
+            Ok(value) if !name.is_empty() => expanded.push_str(&value),
+            _ => return Err(Error::MissingEnvVar(name)),
+        }
+    }
+
+    Ok(expanded)
 }
 
 #[allow(unused)]
@@ -23,3 +162,175 @@ pub fn parse2<T: syn::parse_macro_input::ParseMacroInput>(
     // `impl FnOnce(ParseStream) -> Result<T>`
     T::parse.parse2(token_stream)
 }
+
+/// Prints `tokens` with everything the `serde` feature adds to generated code removed, so tests of
+/// the types themselves hold whether or not it is enabled.
+#[cfg(test)]
+pub fn without_serde(tokens: proc_macro2::TokenStream) -> String {
+    let mut file: syn::File = syn::parse2(tokens).unwrap();
+    strip_serde(&mut file.items);
+    quote::quote!(#file).to_string()
+}
+
+#[cfg(test)]
+fn strip_serde(items: &mut Vec<syn::Item>) {
+    use syn::Item;
+
+    // Serde's trait impls, the helpers used by `deserialize_with` and the module of adapters.
+    items.retain(|item| match item {
+        Item::Impl(item) => !matches!(&item.trait_, Some((_, path, _)) if is_serde(path)),
+        Item::Mod(item) => {
+            let ident = item.ident.to_string();
+            !(ident.starts_with("__") && ident.ends_with("formats"))
+        }
+        _ => true,
+    });
+
+    for item in items.iter_mut() {
+        match item {
+            Item::Struct(item) => {
+                strip_serde_attrs(&mut item.attrs);
+                for field in item.fields.iter_mut() {
+                    strip_serde_attrs(&mut field.attrs);
+                }
+            }
+            Item::Enum(item) => {
+                strip_serde_attrs(&mut item.attrs);
+                for variant in item.variants.iter_mut() {
+                    strip_serde_attrs(&mut variant.attrs);
+                }
+            }
+            Item::Impl(item) => item.items.retain(|item| match item {
+                syn::ImplItem::Method(method) => !method.sig.ident.to_string().starts_with("__"),
+                _ => true,
+            }),
+            Item::Mod(item) => {
+                if let Some((_, items)) = &mut item.content {
+                    strip_serde(items);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    items.retain(|item| !matches!(item, Item::Impl(item) if item.items.is_empty()));
+}
+
+#[cfg(test)]
+fn strip_serde_attrs(attrs: &mut Vec<syn::Attribute>) {
+    use syn::{Meta, NestedMeta};
+
+    attrs.retain(|attr| !attr.path.is_ident("serde"));
+
+    for attr in attrs.iter_mut().filter(|attr| attr.path.is_ident("derive")) {
+        if let Ok(Meta::List(mut list)) = attr.parse_meta() {
+            let len = list.nested.len();
+            list.nested = list
+                .nested
+                .into_iter()
+                .filter(|nested| !matches!(nested, NestedMeta::Meta(meta) if is_serde(meta.path())))
+                .collect();
+            if list.nested.len() < len {
+                *attr = syn::parse_quote!(#[#list]);
+            }
+        }
+    }
+
+    attrs.retain(|attr| {
+        !attr.path.is_ident("derive")
+            || !matches!(attr.parse_meta(), Ok(Meta::List(list)) if list.nested.is_empty())
+    });
+}
+
+#[cfg(test)]
+fn is_serde(path: &syn::Path) -> bool {
+    matches!(path.segments.first(), Some(segment) if segment.ident == "serde")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internals::utils::{document_from_file, expand_env_vars, resolve_path, Format};
+    use crate::internals::Error;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_detect_format() {
+        let detect = |path: &str, contents: &str| Format::detect(Path::new(path), contents);
+
+        assert_eq!(detect("api.json", "openapi: 3.0.0"), Format::Json);
+        assert_eq!(detect("api.YML", "{}"), Format::Yaml);
+        assert_eq!(detect("api", "\n  {\"openapi\": \"3.0.0\"}"), Format::Json);
+        assert_eq!(detect("api.spec", "openapi: 3.0.0"), Format::Yaml);
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let (schema, _) =
+            document_from_file(&resolve_path("./test-resources/test_schema.json").unwrap())
+                .unwrap();
+        let components = schema.components.unwrap();
+
+        assert!(components.schemas.contains_key("TestEnum"));
+        assert!(components.schemas.contains_key("TestStruct"));
+    }
+
+    #[test]
+    fn test_parse_failure() {
+        assert_eq!(
+            document_from_file(&resolve_path("./test-resources/invalid_schema.json").unwrap()),
+            Err(Error::FileParseFailed(
+                concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/test-resources/invalid_schema.json"
+                )
+                .to_string(),
+                "expected `,` or `}` at line 4 column 3".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(
+            resolve_path("./test-resources/test_schema.yaml"),
+            Ok(manifest_dir.join("test-resources/test_schema.yaml"))
+        );
+        assert_eq!(
+            resolve_path("$CARGO_MANIFEST_DIR/../api.yaml"),
+            Ok(manifest_dir.parent().unwrap().join("api.yaml"))
+        );
+        assert_eq!(resolve_path("/api.yaml"), Ok(PathBuf::from("/api.yaml")));
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+
+        assert_eq!(
+            expand_env_vars("${CARGO_MANIFEST_DIR}/api.yaml"),
+            Ok(format!("{}/api.yaml", manifest_dir))
+        );
+        assert_eq!(
+            expand_env_vars("$CARGO_MANIFEST_DIR.yaml"),
+            Ok(format!("{}.yaml", manifest_dir))
+        );
+        assert_eq!(expand_env_vars("$$api.yaml"), Ok("$api.yaml".to_string()));
+        assert_eq!(
+            expand_env_vars("$OPENAPI_TO_RUST_UNSET/api.yaml"),
+            Err(Error::MissingEnvVar("OPENAPI_TO_RUST_UNSET".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/test-resources/missing.yaml");
+        let err = std::fs::File::open(path).unwrap_err();
+
+        assert_eq!(
+            document_from_file(Path::new(path)),
+            Err(Error::FileOpenFailed(path.to_string(), err.to_string()))
+        );
+    }
+}
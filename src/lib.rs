@@ -2,14 +2,21 @@ mod internals;
 
 use internals::Error;
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, AttributeArgs, DeriveInput, Error as SynError};
+use syn::{parse_macro_input, AttributeArgs, DeriveInput, Error as SynError, ItemMod};
 
 #[proc_macro_attribute]
 pub fn from_openapi(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = parse_macro_input!(args as AttributeArgs);
-    let input = parse_macro_input!(input as DeriveInput);
 
-    match internals::from_openapi(&input, &args) {
+    let result = match syn::parse::<ItemMod>(input.clone()) {
+        Ok(module) => internals::from_openapi_module(&module, &args),
+        Err(_) => {
+            let input = parse_macro_input!(input as DeriveInput);
+            internals::from_openapi(&input, &args)
+        }
+    };
+
+    match result {
         Ok(tokens) => tokens,
         Err(err) => <Error as Into<SynError>>::into(err).to_compile_error(),
     }
@@ -27,3 +34,14 @@ pub fn check_openapi(args: TokenStream, input: TokenStream) -> TokenStream {
     }
     .into()
 }
+
+#[proc_macro]
+pub fn generate(args: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+
+    match internals::generate(&args) {
+        Ok(tokens) => tokens,
+        Err(err) => <Error as Into<SynError>>::into(err).to_compile_error(),
+    }
+    .into()
+}
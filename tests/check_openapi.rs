@@ -10,3 +10,25 @@ fn test_check_openapi_success() {
         Bar,
     }
 }
+
+#[test]
+fn test_check_openapi_json() {
+    #[check_openapi(schema = "./test-resources/test_schema.json")]
+    enum TestEnum {
+        #[openapi(value = "foo")]
+        Foo,
+        #[openapi(value = "bar")]
+        Bar,
+    }
+}
+
+#[test]
+fn test_check_openapi_struct() {
+    #[check_openapi(schema = "./test-resources/test_schema.yaml")]
+    #[allow(dead_code)]
+    struct TestStruct {
+        foo: String,
+        #[openapi(allow_type_mismatch)]
+        bar: Option<i64>,
+    }
+}
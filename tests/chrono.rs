@@ -0,0 +1,71 @@
+#![cfg(all(feature = "chrono", feature = "serde"))]
+
+use chrono::{Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use openapi_to_rust::from_openapi;
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestTemporal;
+
+#[test]
+fn test_chrono_round_trip() {
+    let json = concat!(
+        r#"{"date":"2021-03-04","timestamp":"2021-03-04T05:06:07Z","time":"05:06:07","#,
+        r#""timeout":"P1DT2H30M","delay":"PT0.5S","history":["2021-03-04T05:06:07.123Z"]}"#
+    );
+
+    let value: TestTemporal = serde_json::from_str(json).unwrap();
+    assert_eq!(value.date, NaiveDate::from_ymd_opt(2021, 3, 4).unwrap());
+    assert_eq!(
+        value.timestamp,
+        Utc.with_ymd_and_hms(2021, 3, 4, 5, 6, 7).unwrap()
+    );
+    assert_eq!(value.time, NaiveTime::from_hms_opt(5, 6, 7).unwrap());
+    assert_eq!(value.timeout, Duration::hours(26) + Duration::minutes(30));
+    assert_eq!(value.delay, Some(Duration::milliseconds(500)));
+    assert_eq!(serde_json::to_string(&value).unwrap(), json);
+}
+
+#[test]
+fn test_chrono_durations() {
+    let parse = |timeout: &str| {
+        let json = format!(
+            r#"{{"date":"2021-03-04","timestamp":"2021-03-04T05:06:07Z","time":"05:06:07","timeout":"{}"}}"#,
+            timeout
+        );
+        serde_json::from_str::<TestTemporal>(&json).map(|value| value.timeout)
+    };
+
+    assert_eq!(parse("P2W").unwrap(), Duration::days(14));
+    assert_eq!(parse("-PT1M30S").unwrap(), -Duration::seconds(90));
+    assert_eq!(parse("PT0S").unwrap(), Duration::zero());
+    assert!(parse("P1Y").is_err());
+    assert!(parse("PT").is_err());
+    assert!(parse("1H").is_err());
+}
+
+#[from_openapi(schema = "./test-resources/temporal_schema.yaml")]
+mod models {
+    #[derive(Debug, PartialEq)]
+    pub struct Schedule;
+
+    #[derive(Debug, PartialEq)]
+    pub enum Window {}
+}
+
+#[test]
+fn test_chrono_nested_round_trip() {
+    let json = r#"{"timeout":"PT30S","retries":["PT1S","PT0.5S"],"window":"P1D"}"#;
+
+    let value: models::Schedule = serde_json::from_str(json).unwrap();
+    assert_eq!(value.timeout, Duration::seconds(30));
+    assert_eq!(
+        value.retries,
+        Some(vec![Duration::seconds(1), Duration::milliseconds(500)])
+    );
+    assert_eq!(
+        value.window,
+        Some(models::Window::Duration(Duration::days(1)))
+    );
+    assert_eq!(serde_json::to_string(&value).unwrap(), json);
+}
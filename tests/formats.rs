@@ -0,0 +1,36 @@
+#![cfg(all(feature = "serde", feature = "ip", feature = "uuid", feature = "url"))]
+
+use openapi_to_rust::from_openapi;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Email(String);
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml", formats(email = "Email"))]
+#[derive(Debug, PartialEq)]
+struct TestFormats;
+
+#[test]
+fn test_formats_round_trip() {
+    let json = concat!(
+        r#"{"id":"67e55044-10b1-426f-9247-bb680e5fe0c8","homepage":"https://example.com/","#,
+        r#""email":"ada@example.com","address":"127.0.0.1","address_v6":"::1"}"#
+    );
+
+    let value: TestFormats = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        value.id,
+        uuid::Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap()
+    );
+    assert_eq!(
+        value.homepage,
+        Some(url::Url::parse("https://example.com").unwrap())
+    );
+    assert_eq!(value.email, Some(Email("ada@example.com".to_string())));
+    assert_eq!(value.address, Ipv4Addr::LOCALHOST);
+    assert_eq!(value.address_v6, Some(Ipv6Addr::LOCALHOST));
+    assert_eq!(serde_json::to_string(&value).unwrap(), json);
+
+    let invalid = r#"{"id":"not-a-uuid","address":"127.0.0.1"}"#;
+    assert!(serde_json::from_str::<TestFormats>(invalid).is_err());
+}
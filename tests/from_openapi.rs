@@ -0,0 +1,61 @@
+use openapi_to_rust::from_openapi;
+
+#[test]
+fn test_from_openapi_references() {
+    #[from_openapi(schema = "./test-resources/test_schema.yaml")]
+    enum TestEnum {}
+
+    #[from_openapi(schema = "./test-resources/test_schema.yaml")]
+    struct TestStruct;
+
+    #[from_openapi(schema = "./test-resources/test_schema.yaml")]
+    struct TestReferences;
+
+    let references = TestReferences {
+        test_enums: Some(vec![TestEnum::Foo, TestEnum::Bar]),
+        test_struct: TestStruct {
+            foo: "foo".to_string(),
+            bar: None,
+        },
+    };
+
+    assert_eq!(references.test_struct.foo, "foo");
+    assert_eq!(references.test_struct.bar, None);
+    assert_eq!(references.test_enums.map(|e| e.len()), Some(2));
+}
+
+#[test]
+fn test_from_openapi_external_reference() {
+    #[from_openapi(schema = "./test-resources/external_schema.yaml")]
+    struct ExternalStruct;
+
+    #[from_openapi(schema = "./test-resources/test_schema.yaml")]
+    struct TestExternalReference;
+
+    let reference = TestExternalReference {
+        external: ExternalStruct {
+            value: "value".to_string(),
+        },
+    };
+
+    assert_eq!(reference.external.value, "value");
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_from_openapi_docs() {
+    #[from_openapi(schema = "./test-resources/test_schema.yaml")]
+    enum TestDocsKind {}
+
+    #[from_openapi(schema = "./test-resources/test_schema.yaml")]
+    struct TestDocs;
+
+    let docs = TestDocs {
+        name: Some("Rex".to_string()),
+        legacy: None,
+    };
+
+    assert_eq!(docs.name.as_deref(), Some("Rex"));
+    assert_eq!(docs.legacy, None);
+    assert_eq!(TestDocsKind::Plain.as_str(), "plain");
+}
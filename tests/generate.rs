@@ -0,0 +1,73 @@
+mod generated {
+    openapi_to_rust::generate!(schema = "./test-resources/generate_schema.yaml");
+}
+
+#[openapi_to_rust::from_openapi(schema = "./test-resources/generate_schema.yaml")]
+mod models {
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum PetStatus {}
+
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PetId(pub i64);
+}
+
+#[test]
+fn test_generate() {
+    let pet = generated::Pet {
+        id: 1,
+        name: "Rex".to_string(),
+        status: Some(generated::PetStatus::Available),
+        tags: Some(vec![generated::Label { label: None }]),
+    };
+
+    assert_eq!(pet.id, 1);
+    assert_eq!(pet.tags.map(|t| t.len()), Some(1));
+}
+
+#[test]
+fn test_from_openapi_module() {
+    let pet = models::Pet {
+        id: models::PetId(1),
+        name: "Rex".to_string(),
+        status: Some(models::PetStatus::Sold),
+        tags: None,
+    };
+
+    assert_eq!(pet.id.0, 1);
+    assert_eq!(pet.status, Some(models::PetStatus::Sold));
+}
+
+#[test]
+fn test_string_enum_impls() {
+    use std::str::FromStr;
+
+    assert_eq!(
+        models::PetStatus::ALL,
+        &[models::PetStatus::Available, models::PetStatus::Sold]
+    );
+    assert_eq!(models::PetStatus::Sold.as_str(), "sold");
+    assert_eq!(models::PetStatus::Available.to_string(), "available");
+    assert_eq!(
+        models::PetStatus::from_str("sold"),
+        Ok(models::PetStatus::Sold)
+    );
+
+    let error = models::PetStatus::from_str("lost").unwrap_err();
+    assert_eq!(error.to_string(), "unknown PetStatus value `lost`");
+}
+
+mod nested {
+    openapi_to_rust::generate!(schema = "./test-resources/nested_schema.yaml");
+}
+
+#[test]
+fn test_nested_names() {
+    let a = nested::A {
+        b_c: Some(nested::ABC { x: None }),
+    };
+    let ab = nested::AB {
+        c: Some(nested::ABC2 { y: None }),
+    };
+
+    assert!(a.b_c.is_some() && ab.c.is_some());
+}
@@ -0,0 +1,360 @@
+#![cfg(feature = "serde")]
+
+use openapi_to_rust::from_openapi;
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+enum TestEnum {}
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestSerde;
+
+#[test]
+fn test_serde_round_trip() {
+    let value = TestSerde {
+        first_name: "Ada".to_string(),
+        last_name: None,
+        status: Some(TestEnum::Foo),
+    };
+
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(json, r#"{"firstName":"Ada","status":"foo"}"#);
+    assert_eq!(serde_json::from_str::<TestSerde>(&json).unwrap(), value);
+}
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml", tri_state)]
+#[derive(Debug, PartialEq)]
+struct TestNullable;
+
+#[test]
+fn test_serde_tri_state() {
+    let parse = |json| serde_json::from_str::<TestNullable>(json).unwrap();
+
+    assert_eq!(parse(r#"{"name":null}"#).nickname, None);
+    assert_eq!(
+        parse(r#"{"name":null,"nickname":null}"#).nickname,
+        Some(None)
+    );
+    assert_eq!(
+        parse(r#"{"name":"Ada","nickname":"Ada"}"#).nickname,
+        Some(Some("Ada".to_string()))
+    );
+
+    let value = TestNullable {
+        age: None,
+        name: None,
+        nickname: Some(None),
+    };
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        r#"{"name":null,"nickname":null}"#
+    );
+}
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestCat;
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestDog;
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+enum TestPet {}
+
+#[test]
+fn test_serde_one_of() {
+    let cat = TestPet::Cat(TestCat { meow: Some(true) });
+
+    let json = serde_json::to_string(&cat).unwrap();
+    assert_eq!(json, r#"{"petType":"cat","meow":true}"#);
+    assert_eq!(serde_json::from_str::<TestPet>(&json).unwrap(), cat);
+    assert_eq!(
+        serde_json::from_str::<TestPet>(r#"{"petType":"TestDog"}"#).unwrap(),
+        TestPet::TestDog(TestDog { bark: None })
+    );
+}
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestTaggedCat;
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestTaggedDog;
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+enum TestTaggedPet {}
+
+#[test]
+fn test_serde_one_of_declared_tag() {
+    let cat = TestTaggedPet::Cat(TestTaggedCat {
+        pet_type: "cat".to_string(),
+        meow: Some(true),
+    });
+
+    let json = serde_json::to_string(&cat).unwrap();
+    assert_eq!(json, r#"{"petType":"cat","meow":true}"#);
+    assert_eq!(serde_json::from_str::<TestTaggedPet>(&json).unwrap(), cat);
+
+    let dog = TestTaggedPet::Value1(TestTaggedDog {
+        pet_type: "1".to_string(),
+        bark: Some(false),
+    });
+
+    let json = serde_json::to_string(&dog).unwrap();
+    assert_eq!(json, r#"{"petType":"1","bark":false}"#);
+    assert_eq!(serde_json::from_str::<TestTaggedPet>(&json).unwrap(), dog);
+
+    // Alternatives which don't declare the tag still have it added and removed by the union.
+    let untagged = TestTaggedPet::TestDog(TestDog { bark: Some(true) });
+
+    let json = serde_json::to_string(&untagged).unwrap();
+    assert_eq!(json, r#"{"petType":"TestDog","bark":true}"#);
+    assert_eq!(
+        serde_json::from_str::<TestTaggedPet>(&json).unwrap(),
+        untagged
+    );
+
+    // Properties the alternative doesn't know of are buffered along with the rest.
+    let json = r#"{"toys":[1,{"name":null}],"petType":"cat","age":2.5,"meow":false}"#;
+    assert_eq!(
+        serde_json::from_str::<TestTaggedPet>(json).unwrap(),
+        TestTaggedPet::Cat(TestTaggedCat {
+            pet_type: "cat".to_string(),
+            meow: Some(false),
+        })
+    );
+
+    assert!(serde_json::from_str::<TestTaggedPet>(r#"{"petType":"fish"}"#).is_err());
+    assert!(serde_json::from_str::<TestTaggedPet>(r#"{"meow":true}"#).is_err());
+}
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestExtra;
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestStrict;
+
+#[test]
+fn test_serde_additional_properties() {
+    let value = serde_json::from_str::<TestExtra>(r#"{"name":"Ada","age":36}"#).unwrap();
+    assert_eq!(value.name, Some("Ada".to_string()));
+    assert_eq!(value.extra.get("age"), Some(&36));
+
+    assert!(serde_json::from_str::<TestStrict>(r#"{"name":"Ada"}"#).is_ok());
+    assert!(serde_json::from_str::<TestStrict>(r#"{"name":"Ada","age":36}"#).is_err());
+}
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestNested;
+
+#[test]
+fn test_serde_nested() {
+    let json = r#"{"owner":{"name":"Ada","address":{"city":"London"}},"size":"small"}"#;
+    let value = serde_json::from_str::<TestNested>(json).unwrap();
+
+    assert_eq!(
+        value,
+        TestNested {
+            owner: Some(TestNestedOwner2 {
+                address: Some(TestNestedOwner2Address {
+                    city: Some("London".to_string()),
+                }),
+                name: "Ada".to_string(),
+            }),
+            size: Some(TestNestedSize::Small),
+            tags: None,
+        }
+    );
+}
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+enum TestCode {
+    #[openapi(value = 5)]
+    NotFound,
+}
+
+#[test]
+fn test_serde_integer_enum() {
+    assert_eq!(TestCode::NotFound as i64, 5);
+    assert_eq!(serde_json::to_string(&TestCode::Value2).unwrap(), "2");
+    assert_eq!(
+        serde_json::from_str::<TestCode>("5").unwrap(),
+        TestCode::NotFound
+    );
+    assert!(serde_json::from_str::<TestCode>("3").is_err());
+}
+
+#[from_openapi(
+    schema = "./test-resources/test_schema.yaml",
+    ref = "TestEnum",
+    open_enum
+)]
+#[derive(Debug, PartialEq)]
+enum TestOpenEnum {}
+
+#[from_openapi(
+    schema = "./test-resources/test_schema.yaml",
+    ref = "TestCode",
+    open_enum
+)]
+#[derive(Debug, PartialEq)]
+enum TestOpenCode {}
+
+#[test]
+fn test_serde_open_enum() {
+    let parse = |json| serde_json::from_str::<TestOpenEnum>(json).unwrap();
+
+    assert_eq!(parse(r#""foo""#), TestOpenEnum::Foo);
+    assert_eq!(parse(r#""baz""#), TestOpenEnum::Unknown("baz".to_string()));
+    assert_eq!(
+        serde_json::to_string(&TestOpenEnum::Unknown("baz".to_string())).unwrap(),
+        r#""baz""#
+    );
+    assert_eq!("bar".parse(), Ok(TestOpenEnum::Bar));
+
+    assert_eq!(
+        serde_json::from_str::<TestOpenCode>("3").unwrap(),
+        TestOpenCode::Other(3)
+    );
+    assert_eq!(serde_json::to_string(&TestOpenCode::Value5).unwrap(), "5");
+    assert_eq!("1".parse(), Ok(TestOpenCode::Value1));
+}
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestBinary;
+
+#[test]
+fn test_serde_bytes() {
+    let value = TestBinary {
+        signature: b"any carnal pleas".to_vec(),
+        file: [1, 2, 3][..].into(),
+        thumbnail: Some(b"ab".to_vec()),
+    };
+
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(
+        json,
+        r#"{"signature":"YW55IGNhcm5hbCBwbGVhcw==","file":[1,2,3],"thumbnail":"YWI="}"#
+    );
+    assert_eq!(serde_json::from_str::<TestBinary>(&json).unwrap(), value);
+
+    let parse = |signature: &str| {
+        let json = format!(r#"{{"signature":"{}","file":[]}}"#, signature);
+        serde_json::from_str::<TestBinary>(&json).map(|value| value.signature)
+    };
+
+    assert_eq!(
+        parse("YW55IGNhcm5hbCBwbGVhcw").unwrap(),
+        b"any carnal pleas"
+    );
+    assert_eq!(parse("-_8").unwrap(), [0xfb, 0xff]);
+    assert_eq!(parse("").unwrap(), b"");
+    assert!(parse("YWI").is_ok());
+    assert!(parse("Y").is_err());
+    assert!(parse("YW=").is_err());
+    assert!(parse("YW!=").is_err());
+}
+
+#[from_openapi(schema = "./test-resources/bytes_schema.yaml")]
+mod documents {
+    #[derive(Debug, PartialEq)]
+    pub struct Document;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Attachment;
+}
+
+#[test]
+fn test_serde_nested_bytes() {
+    let value = documents::Document {
+        signature: b"ab".to_vec(),
+        attachments: Some(vec![documents::Attachment {
+            parts: Some(vec![b"any carnal pleas".to_vec(), vec![]]),
+            checksums: Some(
+                vec![
+                    ("a".to_string(), Some(vec![0xfb, 0xff])),
+                    ("b".to_string(), None),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+        }]),
+    };
+
+    let json = serde_json::to_string(&value).unwrap();
+    assert!(json.starts_with(
+        r#"{"signature":"YWI=","attachments":[{"parts":["YW55IGNhcm5hbCBwbGVhcw==",""],"#
+    ));
+    assert!(json.contains(r#""a":"+/8=""#));
+    assert!(json.contains(r#""b":null"#));
+    assert_eq!(
+        serde_json::from_str::<documents::Document>(&json).unwrap(),
+        value
+    );
+}
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestOddNames;
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+enum TestOddValues {}
+
+#[test]
+fn test_odd_names_round_trip() {
+    let value = TestOddNames {
+        r#type: "cat".to_string(),
+        r#ref: Some("a".to_string()),
+        value_1st: Some(1),
+        self_: Some(true),
+    };
+
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(json, r#"{"type":"cat","ref":"a","1st":1,"self":true}"#);
+    assert_eq!(serde_json::from_str::<TestOddNames>(&json).unwrap(), value);
+
+    let values = [
+        TestOddValues::Value10,
+        TestOddValues::Value,
+        TestOddValues::ValueSelf,
+    ];
+    let json = serde_json::to_string(&values).unwrap();
+    assert_eq!(json, r#"["1.0","","self"]"#);
+}
+
+#[test]
+fn test_serde_integer_enum_numbers() {
+    let parse = |json| serde_json::from_str::<TestCode>(json);
+
+    assert_eq!(parse("5.0").unwrap(), TestCode::NotFound);
+    assert_eq!(parse("2").unwrap(), TestCode::Value2);
+    assert!(parse("2.5").is_err());
+    assert!(parse("18446744073709551615").is_err());
+
+    let value = serde_json::Value::from(5u64);
+    let code: TestCode = serde_json::from_value(value).unwrap();
+    assert_eq!(
+        serde_json::from_str::<TestCode>(&serde_json::to_string(&code).unwrap()).unwrap(),
+        code
+    );
+
+    assert_eq!(
+        serde_json::from_str::<TestOpenCode>("3.0").unwrap(),
+        TestOpenCode::Other(3)
+    );
+    assert_eq!(
+        serde_json::from_value::<TestOpenCode>(serde_json::Value::from(1u64)).unwrap(),
+        TestOpenCode::Value1
+    );
+}
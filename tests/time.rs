@@ -0,0 +1,114 @@
+#![cfg(all(feature = "time", feature = "serde", not(feature = "chrono")))]
+
+use openapi_to_rust::from_openapi;
+use std::collections::{HashMap, HashSet};
+use time::{Date, Duration, Month, Time, UtcOffset};
+
+#[from_openapi(schema = "./test-resources/test_schema.yaml")]
+#[derive(Debug, PartialEq)]
+struct TestTemporal;
+
+#[test]
+fn test_time_round_trip() {
+    let json = concat!(
+        r#"{"date":"2021-03-04","timestamp":"2021-03-04T05:06:07+01:00","time":"05:06:07.25","#,
+        r#""timeout":"P1DT2H30M","delay":null,"history":["2021-03-04T05:06:07.5Z"]}"#
+    );
+
+    let value: TestTemporal = serde_json::from_str(json).unwrap();
+    let date = Date::from_calendar_date(2021, Month::March, 4).unwrap();
+    assert_eq!(value.date, date);
+    assert_eq!(
+        value.timestamp,
+        date.with_hms(5, 6, 7)
+            .unwrap()
+            .assume_offset(UtcOffset::from_hms(1, 0, 0).unwrap())
+    );
+    assert_eq!(value.time, Time::from_hms_milli(5, 6, 7, 250).unwrap());
+    assert_eq!(value.timeout, Duration::hours(26) + Duration::minutes(30));
+    assert_eq!(value.delay, None);
+    assert_eq!(
+        value.history,
+        Some(vec![date
+            .with_hms_milli(5, 6, 7, 500)
+            .unwrap()
+            .assume_offset(UtcOffset::UTC)])
+    );
+
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(
+        json,
+        concat!(
+            r#"{"date":"2021-03-04","timestamp":"2021-03-04T05:06:07+01:00","time":"05:06:07.25","#,
+            r#""timeout":"P1DT2H30M","history":["2021-03-04T05:06:07.5Z"]}"#
+        )
+    );
+    assert_eq!(serde_json::from_str::<TestTemporal>(&json).unwrap(), value);
+}
+
+#[test]
+fn test_time_invalid() {
+    let json = r#"{"date":"2021-02-30","timestamp":"2021-03-04T05:06:07Z","time":"05:06:07","timeout":"PT1S"}"#;
+
+    let error = serde_json::from_str::<TestTemporal>(json).unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with(r#"invalid value: string "2021-02-30", expected an RFC 3339 date"#));
+}
+
+#[from_openapi(schema = "./test-resources/temporal_schema.yaml", tri_state)]
+mod models {
+    #[derive(Debug, PartialEq)]
+    pub struct Schedule;
+
+    #[derive(Debug, PartialEq)]
+    pub enum Window {}
+}
+
+#[test]
+fn test_time_nested_round_trip() {
+    let json = concat!(
+        r#"{"timeout":"PT30S","retries":["PT1S","PT2S"],"holidays":["2021-12-25"],"#,
+        r#""deadlines":{"launch":"2021-03-04T05:06:07Z"},"reminder":null,"window":"PT1M"}"#
+    );
+
+    let value: models::Schedule = serde_json::from_str(json).unwrap();
+    let date = Date::from_calendar_date(2021, Month::March, 4).unwrap();
+    assert_eq!(value.timeout, Duration::seconds(30));
+    assert_eq!(
+        value.retries,
+        Some(vec![Duration::seconds(1), Duration::seconds(2)])
+    );
+    assert_eq!(
+        value.holidays,
+        Some(HashSet::from([Date::from_calendar_date(
+            2021,
+            Month::December,
+            25
+        )
+        .unwrap()]))
+    );
+    assert_eq!(
+        value.deadlines,
+        Some(HashMap::from([(
+            "launch".to_string(),
+            date.with_hms(5, 6, 7).unwrap().assume_utc()
+        )]))
+    );
+    assert_eq!(value.reminder, Some(None));
+    assert_eq!(
+        value.window,
+        Some(models::Window::Duration(Duration::minutes(1)))
+    );
+    assert_eq!(serde_json::to_string(&value).unwrap(), json);
+
+    let value: models::Schedule =
+        serde_json::from_str(r#"{"timeout":"PT0S","reminder":"2021-03-04T05:06:07Z","window":3}"#)
+            .unwrap();
+    assert_eq!(
+        value.reminder,
+        Some(Some(date.with_hms(5, 6, 7).unwrap().assume_utc()))
+    );
+    assert_eq!(value.window, Some(models::Window::Integer(3)));
+    assert_eq!(value.retries, None);
+}